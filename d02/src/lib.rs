@@ -1,44 +1,174 @@
-pub fn p2(file: &str) -> u32 {
-    file.lines()
-        // parse a round as pair of moves
-        .map(|round| round.split_once(' ').unwrap())
-        .map(|(opp_move, your_move)| {
-            // score based on what you played
-            (match (opp_move, your_move) {
-                ("A", "Y") | ("B", "X") | ("C", "Z") => 1,
-                ("A", "Z") | ("B", "Y") | ("C", "X") => 2,
-                ("A", "X") | ("B", "Z") | ("C", "Y") => 3,
-                _ => unreachable!(),
-            })
-            // score based on round outcome
-            + match your_move {
-                "X" => 0,
-                "Y" => 3,
-                "Z" => 6,
-                _ => unreachable!(),
-            }
-        })
-        .sum()
-}
-
-pub fn p1(file: &str) -> u32 {
-    file.lines()
-        .map(|round| round.split_once(' ').unwrap())
-        .map(|(opp_move, your_move)| {
-            // score based on round outcome
-            (match (opp_move, your_move) {
-            ("A", "Z") | ("B", "X") | ("C", "Y") => 0,
-            ("A", "X") | ("B", "Y") | ("C", "Z") => 3,
-            ("A", "Y") | ("B", "Z") | ("C", "X") => 6,
-            _ => unreachable!(),
-        })
-        // score based on what you played
-        + match your_move {
-            "X" => 1,
-            "Y" => 2,
-            "Z" => 3,
-            _ => unreachable!(),
+use std::str::FromStr;
+
+use anyhow::{bail, Context};
+
+pub mod tournament;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum Shape {
+    Rock,
+    Paper,
+    Scissors,
+}
+
+impl Shape {
+    pub const ALL: [Shape; 3] = [Shape::Rock, Shape::Paper, Shape::Scissors];
+
+    fn score(self) -> u32 {
+        match self {
+            Shape::Rock => 1,
+            Shape::Paper => 2,
+            Shape::Scissors => 3,
+        }
+    }
+
+    /// The shape that loses against this one
+    fn beats(self) -> Self {
+        match self {
+            Shape::Rock => Shape::Scissors,
+            Shape::Paper => Shape::Rock,
+            Shape::Scissors => Shape::Paper,
+        }
+    }
+
+    /// The shape that wins against this one
+    fn loses_to(self) -> Self {
+        match self {
+            Shape::Rock => Shape::Paper,
+            Shape::Paper => Shape::Scissors,
+            Shape::Scissors => Shape::Rock,
+        }
+    }
+
+    /// What to play against `opponent` to get the desired `outcome`
+    pub fn for_outcome(opponent: Self, outcome: Outcome) -> Self {
+        match outcome {
+            Outcome::Loss => opponent.beats(),
+            Outcome::Draw => opponent,
+            Outcome::Win => opponent.loses_to(),
+        }
+    }
+}
+
+impl FromStr for Shape {
+    type Err = anyhow::Error;
+
+    // the opponent's column uses A/B/C, and the first reading of yours uses X/Y/Z
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "A" | "X" => Ok(Shape::Rock),
+            "B" | "Y" => Ok(Shape::Paper),
+            "C" | "Z" => Ok(Shape::Scissors),
+            s => bail!("invalid shape: {s}"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Outcome {
+    Loss,
+    Draw,
+    Win,
+}
+
+impl Outcome {
+    pub fn of(opponent: Shape, you: Shape) -> Self {
+        if you == opponent {
+            Outcome::Draw
+        } else if you.beats() == opponent {
+            Outcome::Win
+        } else {
+            Outcome::Loss
+        }
+    }
+
+    fn score(self) -> u32 {
+        match self {
+            Outcome::Loss => 0,
+            Outcome::Draw => 3,
+            Outcome::Win => 6,
+        }
+    }
+}
+
+impl FromStr for Outcome {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "X" => Ok(Outcome::Loss),
+            "Y" => Ok(Outcome::Draw),
+            "Z" => Ok(Outcome::Win),
+            s => bail!("invalid outcome: {s}"),
         }
+    }
+}
+
+/// Score of a single round from your point of view
+pub fn round_score(opponent: Shape, you: Shape) -> u32 {
+    // score based on what you played + score based on round outcome
+    you.score() + Outcome::of(opponent, you).score()
+}
+
+/// How to understand the second column of the strategy guide
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Reading {
+    /// X/Y/Z is the shape you should play (part 1)
+    Shape,
+    /// X/Y/Z is the outcome the round should have (part 2)
+    Outcome,
+}
+
+pub struct StrategyGuide {
+    /// what the guide says the opponent will play
+    predicted: Vec<Shape>,
+    /// what the guide says you should play in response
+    responses: Vec<Shape>,
+}
+
+impl StrategyGuide {
+    pub fn read(file: &str, reading: Reading) -> anyhow::Result<Self> {
+        let (predicted, responses) = file
+            .lines()
+            // parse a round as pair of moves
+            .map(|round| -> anyhow::Result<(Shape, Shape)> {
+                let (opp_move, your_move) = round.split_once(' ').context("expected space")?;
+                let opp_move = Shape::from_str(opp_move)?;
+                let your_move = match reading {
+                    Reading::Shape => Shape::from_str(your_move)?,
+                    Reading::Outcome => Shape::for_outcome(opp_move, Outcome::from_str(your_move)?),
+                };
+                Ok((opp_move, your_move))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?
+            .into_iter()
+            .unzip();
+        Ok(Self {
+            predicted,
+            responses,
         })
-        .sum()
+    }
+
+    pub fn predicted(&self) -> &[Shape] {
+        &self.predicted
+    }
+
+    pub fn responses(&self) -> &[Shape] {
+        &self.responses
+    }
+
+    /// Total score if the opponent plays exactly as predicted
+    pub fn score(&self) -> u32 {
+        std::iter::zip(&self.predicted, &self.responses)
+            .map(|(&opp_move, &your_move)| round_score(opp_move, your_move))
+            .sum()
+    }
+}
+
+pub fn p1(file: &str) -> anyhow::Result<u32> {
+    Ok(StrategyGuide::read(file, Reading::Shape)?.score())
+}
+
+pub fn p2(file: &str) -> anyhow::Result<u32> {
+    Ok(StrategyGuide::read(file, Reading::Outcome)?.score())
 }
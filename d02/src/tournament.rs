@@ -0,0 +1,347 @@
+use anyhow::ensure;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{round_score, Outcome, Shape, StrategyGuide};
+
+/// Probabilities of the opponent playing rock, paper and scissors
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Distribution([f64; 3]);
+
+impl Distribution {
+    pub fn new(rock: f64, paper: f64, scissors: f64) -> anyhow::Result<Self> {
+        let probabilities = [rock, paper, scissors];
+        ensure!(
+            probabilities.iter().all(|p| (0.0..=1.0).contains(p)),
+            "probabilities must lie in [0, 1]: {probabilities:?}"
+        );
+        ensure!(
+            (probabilities.iter().sum::<f64>() - 1.0).abs() < 1e-9,
+            "probabilities must add up to 1: {probabilities:?}"
+        );
+        Ok(Self(probabilities))
+    }
+
+    pub fn uniform() -> Self {
+        Self([1.0 / 3.0; 3])
+    }
+
+    pub fn pure(shape: Shape) -> Self {
+        let mut probabilities = [0.0; 3];
+        probabilities[shape as usize] = 1.0;
+        Self(probabilities)
+    }
+
+    pub fn probability(&self, shape: Shape) -> f64 {
+        self.0[shape as usize]
+    }
+
+    /// Plays `self` with probability `weight`, and `other` otherwise
+    fn mix(self, other: Self, weight: f64) -> Self {
+        Self(std::array::from_fn(|i| {
+            weight * self.0[i] + (1.0 - weight) * other.0[i]
+        }))
+    }
+
+    fn sample(&self, rng: &mut impl Rng) -> Shape {
+        let mut roll: f64 = rng.gen();
+        for shape in Shape::ALL {
+            roll -= self.probability(shape);
+            if roll < 0.0 {
+                return shape;
+            }
+        }
+        // only reachable through rounding errors - fall back to the last possible shape
+        *Shape::ALL
+            .iter()
+            .rev()
+            .find(|&&shape| self.probability(shape) > 0.0)
+            .unwrap()
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Round {
+    pub opponent: Shape,
+    pub you: Shape,
+}
+
+impl Round {
+    /// The shape that won the round - in case of a draw, the one both played
+    pub fn winner(self) -> Shape {
+        match Outcome::of(self.opponent, self.you) {
+            Outcome::Loss => self.opponent,
+            Outcome::Draw | Outcome::Win => self.you,
+        }
+    }
+}
+
+/// The previous round, which is all the history the opponents below react to
+type LastRound = Option<Round>;
+
+const NUM_LAST_ROUNDS: usize = 1 + 3 * 3;
+
+fn last_round_idx(last_round: LastRound) -> usize {
+    match last_round {
+        None => 0,
+        Some(Round { opponent, you }) => 1 + 3 * opponent as usize + you as usize,
+    }
+}
+
+fn last_rounds() -> impl Iterator<Item = LastRound> {
+    std::iter::once(None).chain(
+        Shape::ALL
+            .into_iter()
+            .flat_map(|opponent| Shape::ALL.map(|you| Some(Round { opponent, you }))),
+    )
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum OpponentPolicy {
+    /// Plays exactly what the strategy guide predicts
+    AsPredicted,
+    /// Draws every shape from the same distribution, regardless of the history
+    Fixed(Distribution),
+    /// With probability `stickiness` plays the shape that won the previous round,
+    /// otherwise (and in the first round) draws from `otherwise`
+    RepeatLastWinner {
+        stickiness: f64,
+        otherwise: Distribution,
+    },
+}
+
+impl OpponentPolicy {
+    fn distribution(&self, predicted: Shape, last_round: LastRound) -> Distribution {
+        match (self, last_round) {
+            (OpponentPolicy::AsPredicted, _) => Distribution::pure(predicted),
+            (OpponentPolicy::Fixed(distribution), _)
+            | (
+                OpponentPolicy::RepeatLastWinner {
+                    otherwise: distribution,
+                    ..
+                },
+                None,
+            ) => *distribution,
+            (
+                OpponentPolicy::RepeatLastWinner {
+                    stickiness,
+                    otherwise,
+                },
+                Some(round),
+            ) => Distribution::pure(round.winner()).mix(*otherwise, *stickiness),
+        }
+    }
+}
+
+/// What you play in each round, depending on how the previous one went
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Strategy(Vec<[Shape; NUM_LAST_ROUNDS]>);
+
+impl Strategy {
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn shape(&self, round: usize, last_round: Option<Round>) -> Shape {
+        self.0[round][last_round_idx(last_round)]
+    }
+}
+
+impl From<&StrategyGuide> for Strategy {
+    /// Follows the guide blindly, whatever the opponent did before
+    fn from(guide: &StrategyGuide) -> Self {
+        Self(
+            guide
+                .responses()
+                .iter()
+                .map(|&shape| [shape; NUM_LAST_ROUNDS])
+                .collect(),
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Summary {
+    pub mean: f64,
+    /// sample variance of the tournament totals
+    pub variance: f64,
+}
+
+pub struct BestResponse {
+    pub strategy: Strategy,
+    pub expected_score: f64,
+}
+
+/// A series of rounds following a strategy guide, played against a simulated opponent
+pub struct Tournament<'a> {
+    predicted: &'a [Shape],
+    policy: OpponentPolicy,
+}
+
+impl<'a> Tournament<'a> {
+    pub fn new(guide: &'a StrategyGuide, policy: OpponentPolicy) -> anyhow::Result<Self> {
+        if let OpponentPolicy::RepeatLastWinner { stickiness, .. } = policy {
+            ensure!(
+                (0.0..=1.0).contains(&stickiness),
+                "stickiness must lie in [0, 1]: {stickiness}"
+            );
+        }
+        Ok(Self {
+            predicted: guide.predicted(),
+            policy,
+        })
+    }
+
+    /// Total score of a single tournament
+    fn play(&self, strategy: &Strategy, rng: &mut impl Rng) -> u32 {
+        let mut last_round = None;
+        let mut total = 0;
+        for (round_idx, &predicted) in self.predicted.iter().enumerate() {
+            let round = Round {
+                opponent: self.policy.distribution(predicted, last_round).sample(rng),
+                you: strategy.shape(round_idx, last_round),
+            };
+            total += round_score(round.opponent, round.you);
+            last_round = Some(round);
+        }
+        total
+    }
+
+    /// Plays `num_tournaments` tournaments and summarizes their total scores
+    pub fn simulate(
+        &self,
+        strategy: &Strategy,
+        num_tournaments: u32,
+        seed: u64,
+    ) -> anyhow::Result<Summary> {
+        ensure!(
+            strategy.len() == self.predicted.len(),
+            "strategy has {} rounds, but the guide has {}",
+            strategy.len(),
+            self.predicted.len()
+        );
+        ensure!(num_tournaments > 0, "need at least one tournament");
+
+        let mut rng = StdRng::seed_from_u64(seed);
+
+        // Welford's online algorithm
+        let mut mean = 0.0;
+        let mut sum_of_squared_deviations = 0.0;
+        for n in 1..=num_tournaments {
+            let total = f64::from(self.play(strategy, &mut rng));
+            let delta = total - mean;
+            mean += delta / f64::from(n);
+            sum_of_squared_deviations += delta * (total - mean);
+        }
+        let variance = if num_tournaments > 1 {
+            sum_of_squared_deviations / f64::from(num_tournaments - 1)
+        } else {
+            0.0
+        };
+
+        Ok(Summary { mean, variance })
+    }
+
+    /// The strategy maximizing the expected total score, found by backwards induction over the rounds
+    pub fn best_response(&self) -> BestResponse {
+        // expected score of the rounds still to come, given how the last one went
+        let mut expected_rest = [0.0; NUM_LAST_ROUNDS];
+        let mut strategy = vec![[Shape::Rock; NUM_LAST_ROUNDS]; self.predicted.len()];
+
+        for (round_idx, &predicted) in self.predicted.iter().enumerate().rev() {
+            let mut expected_from_here = [0.0; NUM_LAST_ROUNDS];
+            for last_round in last_rounds() {
+                let distribution = self.policy.distribution(predicted, last_round);
+                let expected_with = |you: Shape| -> f64 {
+                    Shape::ALL
+                        .into_iter()
+                        .map(|opponent| {
+                            let round = Some(Round { opponent, you });
+                            distribution.probability(opponent)
+                                * (f64::from(round_score(opponent, you))
+                                    + expected_rest[last_round_idx(round)])
+                        })
+                        .sum()
+                };
+                let (best_shape, best_expected) = Shape::ALL
+                    .into_iter()
+                    .map(|you| (you, expected_with(you)))
+                    .max_by(|(_, e1), (_, e2)| e1.total_cmp(e2))
+                    .unwrap();
+
+                strategy[round_idx][last_round_idx(last_round)] = best_shape;
+                expected_from_here[last_round_idx(last_round)] = best_expected;
+            }
+            expected_rest = expected_from_here;
+        }
+
+        BestResponse {
+            strategy: Strategy(strategy),
+            expected_score: expected_rest[last_round_idx(None)],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Reading;
+    const EXAMPLE: &str = "A Y\nB X\nC Z\n";
+
+    #[test]
+    fn as_predicted_matches_puzzle_scores() {
+        for (reading, score) in [(Reading::Shape, 15.0), (Reading::Outcome, 12.0)] {
+            let guide = StrategyGuide::read(EXAMPLE, reading).unwrap();
+            let tournament = Tournament::new(&guide, OpponentPolicy::AsPredicted).unwrap();
+            let summary = tournament.simulate(&(&guide).into(), 100, 0).unwrap();
+            assert_eq!(
+                summary,
+                Summary {
+                    mean: score,
+                    variance: 0.0
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn best_response_to_prediction_always_wins() {
+        let guide = StrategyGuide::read(EXAMPLE, Reading::Shape).unwrap();
+        let tournament = Tournament::new(&guide, OpponentPolicy::AsPredicted).unwrap();
+        let expected_score = tournament.best_response().expected_score;
+        assert!((expected_score - (8.0 + 9.0 + 7.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn simulation_converges_to_best_response() {
+        let guide = StrategyGuide::read(EXAMPLE, Reading::Shape).unwrap();
+        let policy = OpponentPolicy::RepeatLastWinner {
+            stickiness: 0.7,
+            otherwise: Distribution::new(0.5, 0.3, 0.2).unwrap(),
+        };
+        let tournament = Tournament::new(&guide, policy).unwrap();
+        let best_response = tournament.best_response();
+        let summary = tournament
+            .simulate(&best_response.strategy, 100_000, 42)
+            .unwrap();
+        assert!((summary.mean - best_response.expected_score).abs() < 0.05);
+
+        let guide_summary = tournament.simulate(&(&guide).into(), 100_000, 42).unwrap();
+        assert!(guide_summary.mean < summary.mean);
+    }
+
+    #[test]
+    fn invalid_stickiness() {
+        let guide = StrategyGuide::read(EXAMPLE, Reading::Shape).unwrap();
+        for stickiness in [-0.1, 1.1, f64::NAN] {
+            let policy = OpponentPolicy::RepeatLastWinner {
+                stickiness,
+                otherwise: Distribution::uniform(),
+            };
+            assert!(Tournament::new(&guide, policy).is_err());
+        }
+    }
+}
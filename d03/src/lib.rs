@@ -1,47 +1,369 @@
-use std::collections::HashSet;
+use std::{fmt::Display, num::NonZeroUsize, ops::RangeInclusive, str::FromStr};
 
-use intersection::hash_set;
 use itertools::Itertools;
 
-fn to_priority(char: char) -> usize {
+pub mod reorganize;
+
+fn to_priority(char: char) -> Option<usize> {
     match char {
-        'a'..='z' => (char as usize) - 96,
-        'A'..='Z' => (char as usize) - 64 + 26,
-        _ => unreachable!(),
+        'a'..='z' => Some((char as usize) - 96),
+        'A'..='Z' => Some((char as usize) - 64 + 26),
+        _ => None,
+    }
+}
+
+/// Set of item types, stored as a bitmask where the n-th bit stands for the item with priority n
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct ItemSet(u64);
+
+impl ItemSet {
+    /// Priorities go from 1 to 52
+    const PRIORITIES: RangeInclusive<usize> = 1..=52;
+
+    pub fn insert(&mut self, priority: usize) {
+        assert!(
+            Self::PRIORITIES.contains(&priority),
+            "no item has priority {priority}"
+        );
+        self.0 |= 1 << priority;
+    }
+
+    pub fn contains(self, priority: usize) -> bool {
+        Self::PRIORITIES.contains(&priority) && self.0 & (1 << priority) != 0
+    }
+
+    #[must_use]
+    pub fn intersection(self, other: Self) -> Self {
+        Self(self.0 & other.0)
+    }
+
+    #[must_use]
+    pub fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    pub fn len(self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Priorities of the items in the set, in ascending order
+    pub fn priorities(self) -> Priorities {
+        Priorities(self.0)
+    }
+
+    /// The priority of the only item in the set, if there's exactly one
+    pub fn exactly_one(self) -> Option<usize> {
+        (self.len() == 1).then(|| self.0.trailing_zeros() as usize)
     }
 }
 
-pub fn p1(file: &str) -> usize {
+/// An item that isn't a letter
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct InvalidItem(pub char);
+
+impl Display for InvalidItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid item: {}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidItem {}
+
+impl FromStr for ItemSet {
+    type Err = InvalidItem;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut items = Self::default();
+        for item in s.chars() {
+            items.insert(to_priority(item).ok_or(InvalidItem(item))?);
+        }
+        Ok(items)
+    }
+}
+
+pub struct Priorities(u64);
+
+impl Iterator for Priorities {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.0 == 0 {
+            return None;
+        }
+        let priority = self.0.trailing_zeros() as usize;
+        // clear the lowest set bit
+        self.0 &= self.0 - 1;
+        Some(priority)
+    }
+}
+
+/// What's wrong with a rucksack or a group of rucksacks.
+/// Line numbers start at 1, as in an editor
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Diagnostic {
+    InvalidItem {
+        line: usize,
+        item: char,
+    },
+    OddLength {
+        line: usize,
+        len: usize,
+    },
+    IncompleteGroup {
+        lines: Vec<usize>,
+    },
+    NoCommonItem {
+        lines: Vec<usize>,
+    },
+    SeveralCommonItems {
+        lines: Vec<usize>,
+        priorities: Vec<usize>,
+    },
+    NoBalancedSplit {
+        line: usize,
+    },
+}
+
+/// `line 3` or `lines 1, 2, 3`
+struct Lines<'a>(&'a [usize]);
+
+impl Display for Lines<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            [line] => write!(f, "line {line}"),
+            lines => write!(f, "lines {}", lines.iter().join(", ")),
+        }
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Diagnostic::InvalidItem { line, item } => {
+                write!(f, "line {line}: invalid item {item:?}")
+            }
+            Diagnostic::OddLength { line, len } => {
+                write!(f, "line {line}: odd number of items ({len})")
+            }
+            Diagnostic::IncompleteGroup { lines } => {
+                write!(f, "{}: incomplete group", Lines(lines))
+            }
+            Diagnostic::NoCommonItem { lines } => {
+                write!(f, "{}: no common item", Lines(lines))
+            }
+            Diagnostic::SeveralCommonItems { lines, priorities } => write!(
+                f,
+                "{}: several common items (priorities {})",
+                Lines(lines),
+                priorities.iter().join(", ")
+            ),
+            Diagnostic::NoBalancedSplit { line } => write!(
+                f,
+                "line {line}: item types can't be split into equally sized compartments"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Diagnostic {}
+
+fn items(line: usize, s: &str) -> Result<ItemSet, Diagnostic> {
+    ItemSet::from_str(s).map_err(|InvalidItem(item)| Diagnostic::InvalidItem { line, item })
+}
+
+/// Priority of the only item in `common`, shared by the rucksacks on `lines`
+fn the_common_item(common: ItemSet, lines: Vec<usize>) -> Result<usize, Diagnostic> {
+    match common.len() {
+        0 => Err(Diagnostic::NoCommonItem { lines }),
+        1 => Ok(common.exactly_one().unwrap()),
+        _ => Err(Diagnostic::SeveralCommonItems {
+            lines,
+            priorities: common.priorities().collect(),
+        }),
+    }
+}
+
+/// For each rucksack, the priority of the item found in both of its compartments
+pub fn misplaced_items(file: &str) -> Vec<Result<usize, Diagnostic>> {
     file.lines()
-        // split into compartments
-        .map(|rucksack| rucksack.split_at(rucksack.len() / 2))
-        .map(|(compartment1, compartment2)| (compartment1, compartment2).into())
-        // find the common item
-        .map(|compartments: [&str; 2]| {
-            compartments.map(|compartment| compartment.chars().collect::<HashSet<char>>())
+        .zip(1..)
+        .map(|(rucksack, line)| {
+            // check the items first - as they're all ASCII letters then, bytes and items are the same
+            items(line, rucksack)?;
+            if !rucksack.len().is_multiple_of(2) {
+                return Err(Diagnostic::OddLength {
+                    line,
+                    len: rucksack.len(),
+                });
+            }
+            // split into compartments
+            let (compartment1, compartment2) = rucksack.split_at(rucksack.len() / 2);
+            let common = items(line, compartment1)?.intersection(items(line, compartment2)?);
+            the_common_item(common, vec![line])
         })
-        .map(|[compartment1, compartment2]| {
-            *compartment1
-                .intersection(&compartment2)
-                .exactly_one()
-                .unwrap()
-        })
-        // calculate its priority
-        .map(to_priority)
-        // add up the priorities
-        .sum()
+        .collect()
 }
 
-pub fn p2(file: &str) -> usize {
+/// For each group of `group_size` consecutive rucksacks, the priority of their badge
+pub fn badges(file: &str, group_size: NonZeroUsize) -> Vec<Result<usize, Diagnostic>> {
+    let group_size = group_size.get();
     file.lines()
-        .map(|rucksack| rucksack.chars().collect::<HashSet<char>>())
-        // get chunks of 3 backpacks
-        .chunks(3)
+        .zip(1..)
+        .chunks(group_size)
         .into_iter()
-        // in each chunk, find the common item (the badge)
-        .map(|chunk| hash_set::intersection(chunk).drain().exactly_one().unwrap())
-        // calculate its priority
-        .map(to_priority)
-        // add up the priorities
-        .sum()
+        .map(|group| {
+            let group = group.collect_vec();
+            let lines = group.iter().map(|&(_, line)| line).collect_vec();
+            if group.len() < group_size {
+                return Err(Diagnostic::IncompleteGroup { lines });
+            }
+            // find the common item (the badge)
+            let common = group
+                .into_iter()
+                .map(|(rucksack, line)| items(line, rucksack))
+                .reduce(|items1, items2| Ok(items1?.intersection(items2?)))
+                .unwrap()?;
+            the_common_item(common, lines)
+        })
+        .collect()
+}
+
+pub fn p1(file: &str) -> anyhow::Result<usize> {
+    // add up the priorities
+    Ok(misplaced_items(file).into_iter().sum::<Result<_, _>>()?)
+}
+
+const ELVES_PER_GROUP: NonZeroUsize = NonZeroUsize::new(3).unwrap();
+
+pub fn p2(file: &str) -> anyhow::Result<usize> {
+    // add up the priorities
+    Ok(badges(file, ELVES_PER_GROUP)
+        .into_iter()
+        .sum::<Result<_, _>>()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+    const EXAMPLE: &str = include_str!("../inputs/example.txt");
+
+    fn group_size(group_size: usize) -> NonZeroUsize {
+        NonZeroUsize::new(group_size).unwrap()
+    }
+
+    #[test_case(EXAMPLE => 157; "example")]
+    fn test_p1(inp: &str) -> usize {
+        p1(inp).unwrap()
+    }
+
+    #[test_case(EXAMPLE => 70; "example")]
+    fn test_p2(inp: &str) -> usize {
+        p2(inp).unwrap()
+    }
+
+    #[test]
+    fn item_set() {
+        let items = ItemSet::from_str("aAzZa").unwrap();
+        assert_eq!(items.priorities().collect_vec(), vec![1, 26, 27, 52]);
+        assert_eq!(items.len(), 4);
+        assert!(items.contains(27));
+        assert!(!items.contains(2));
+        assert!(!items.contains(0));
+        assert!(!items.contains(64));
+        assert!(ItemSet::from_str("").unwrap().is_empty());
+        assert_eq!(ItemSet::from_str("ab1"), Err(InvalidItem('1')));
+    }
+
+    #[test]
+    #[should_panic(expected = "no item has priority 70")]
+    fn insert_out_of_range() {
+        ItemSet::default().insert(70);
+    }
+
+    #[test]
+    fn set_operations() {
+        let abc = ItemSet::from_str("abc").unwrap();
+        let bcd = ItemSet::from_str("bcd").unwrap();
+        assert_eq!(abc.intersection(bcd), ItemSet::from_str("cb").unwrap());
+        assert_eq!(abc.union(bcd), ItemSet::from_str("abcd").unwrap());
+        assert_eq!(abc.intersection(bcd).exactly_one(), None);
+        assert_eq!(
+            abc.intersection(ItemSet::from_str("xyzC").unwrap())
+                .exactly_one(),
+            None
+        );
+        assert_eq!(
+            abc.intersection(ItemSet::from_str("Zc").unwrap())
+                .exactly_one(),
+            Some(3)
+        );
+    }
+
+    #[test]
+    fn misplaced_item_diagnostics() {
+        assert_eq!(
+            misplaced_items("abc\nabab\naa\na!a!\né\naéa\n"),
+            vec![
+                Err(Diagnostic::OddLength { line: 1, len: 3 }),
+                Err(Diagnostic::SeveralCommonItems {
+                    lines: vec![2],
+                    priorities: vec![1, 2]
+                }),
+                Ok(1),
+                Err(Diagnostic::InvalidItem { line: 4, item: '!' }),
+                Err(Diagnostic::InvalidItem {
+                    line: 5, item: 'é'
+                }),
+                Err(Diagnostic::InvalidItem {
+                    line: 6, item: 'é'
+                }),
+            ]
+        );
+        assert_eq!(
+            misplaced_items("abcd"),
+            vec![Err(Diagnostic::NoCommonItem { lines: vec![1] })]
+        );
+    }
+
+    #[test]
+    fn badge_diagnostics() {
+        assert_eq!(
+            badges("ab\nac\nad\nbc\nbd\nxy\nzz\n", group_size(3)),
+            vec![
+                Ok(1),
+                Err(Diagnostic::NoCommonItem {
+                    lines: vec![4, 5, 6]
+                }),
+                Err(Diagnostic::IncompleteGroup { lines: vec![7] }),
+            ]
+        );
+        assert_eq!(
+            badges("abc\nabd\n", group_size(2)),
+            vec![Err(Diagnostic::SeveralCommonItems {
+                lines: vec![1, 2],
+                priorities: vec![1, 2]
+            })]
+        );
+        assert_eq!(
+            badges("a1\na", group_size(2)),
+            vec![Err(Diagnostic::InvalidItem { line: 1, item: '1' })]
+        );
+    }
+
+    #[test]
+    fn reports_the_first_problem() {
+        assert_eq!(
+            p1("abab\nabc").unwrap_err().to_string(),
+            "line 1: several common items (priorities 1, 2)"
+        );
+        assert_eq!(
+            p2("ab\nac\nbd").unwrap_err().to_string(),
+            "lines 1, 2, 3: no common item"
+        );
+    }
 }
@@ -0,0 +1,267 @@
+use crate::{to_priority, Diagnostic};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Compartment {
+    First,
+    Second,
+}
+
+/// Move `count` items of type `item` from one compartment to the other
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Move {
+    pub item: char,
+    pub count: usize,
+    pub from: Compartment,
+    pub to: Compartment,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Plan {
+    pub moves: Vec<Move>,
+}
+
+impl Plan {
+    pub fn num_moves(&self) -> usize {
+        self.moves.iter().map(|m| m.count).sum()
+    }
+
+    /// Sum of the priorities of all moved items
+    pub fn priority_cost(&self) -> usize {
+        self.moves
+            .iter()
+            .map(|m| m.count * to_priority(m.item).unwrap())
+            .sum()
+    }
+}
+
+/// Number of moves and total priority of the moved items, if reachable at all
+type Cost = Option<(usize, usize)>;
+
+/// How many items of a type each compartment holds
+struct ItemCounts {
+    item: char,
+    first: usize,
+    second: usize,
+}
+
+/// Cheapest plan for a single rucksack, measured first in moves and then in priorities
+fn plan(line: usize, rucksack: &str) -> Result<Plan, Diagnostic> {
+    let items = rucksack
+        .chars()
+        .map(|item| {
+            let priority = to_priority(item).ok_or(Diagnostic::InvalidItem { line, item })?;
+            Ok((item, priority))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if !items.len().is_multiple_of(2) {
+        return Err(Diagnostic::OddLength {
+            line,
+            len: items.len(),
+        });
+    }
+    let compartment_len = items.len() / 2;
+
+    // count the items of each type, indexed by priority
+    let mut counts: Vec<Option<ItemCounts>> = (0..=52).map(|_| None).collect();
+    for (idx, &(item, priority)) in items.iter().enumerate() {
+        let counts = counts[priority].get_or_insert(ItemCounts {
+            item,
+            first: 0,
+            second: 0,
+        });
+        if idx < compartment_len {
+            counts.first += 1;
+        } else {
+            counts.second += 1;
+        }
+    }
+    let counts = counts.into_iter().flatten().collect::<Vec<_>>();
+
+    // Each item type ends up wholly in one compartment, and the first compartment has to end up
+    // with exactly `compartment_len` items - a knapsack over the item types.
+    // `cheapest[i][len]` is the cost of distributing the first `i` item types such that
+    // `len` items land in the first compartment
+    let add = |cost: Cost, count: usize, item: char| {
+        cost.map(|(moves, priorities)| {
+            (
+                moves + count,
+                priorities + count * to_priority(item).unwrap(),
+            )
+        })
+    };
+    let mut cheapest: Vec<Vec<Cost>> = vec![vec![None; compartment_len + 1]; counts.len() + 1];
+    cheapest[0][0] = Some((0, 0));
+    for (i, c) in counts.iter().enumerate() {
+        for len in 0..=compartment_len {
+            // keep all in the second compartment
+            let to_second = add(cheapest[i][len], c.first, c.item);
+            // keep all in the first compartment
+            let to_first = (len >= c.first + c.second)
+                .then(|| add(cheapest[i][len - c.first - c.second], c.second, c.item))
+                .flatten();
+            cheapest[i + 1][len] = to_second.into_iter().chain(to_first).min();
+        }
+    }
+
+    if cheapest[counts.len()][compartment_len].is_none() {
+        return Err(Diagnostic::NoBalancedSplit { line });
+    }
+
+    // go back through the table to find out where each item type went
+    let mut moves = Vec::new();
+    let mut len = compartment_len;
+    for (i, c) in counts.iter().enumerate().rev() {
+        let best = cheapest[i + 1][len];
+        if add(cheapest[i][len], c.first, c.item) == best {
+            if c.first > 0 {
+                moves.push(Move {
+                    item: c.item,
+                    count: c.first,
+                    from: Compartment::First,
+                    to: Compartment::Second,
+                });
+            }
+        } else {
+            len -= c.first + c.second;
+            if c.second > 0 {
+                moves.push(Move {
+                    item: c.item,
+                    count: c.second,
+                    from: Compartment::Second,
+                    to: Compartment::First,
+                });
+            }
+        }
+    }
+    moves.reverse();
+
+    Ok(Plan { moves })
+}
+
+/// For each rucksack, the cheapest way to move items between its compartments
+/// so that no item type is found in both, while keeping the compartments the same size
+pub fn reorganize(file: &str) -> Vec<Result<Plan, Diagnostic>> {
+    file.lines()
+        .zip(1..)
+        .map(|(rucksack, line)| plan(line, rucksack))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    const EXAMPLE: &str = include_str!("../inputs/example.txt");
+
+    /// The cheapest cost over every way to assign the item types to the compartments
+    fn brute_force(rucksack: &str) -> Cost {
+        let (first, second) = rucksack.split_at(rucksack.len() / 2);
+        let mut types: Vec<char> = rucksack.chars().collect();
+        types.sort_unstable();
+        types.dedup();
+        (0..1_u32 << types.len())
+            .filter_map(|in_first| {
+                let mut first_len = 0;
+                let mut cost = (0, 0);
+                for (idx, &item) in types.iter().enumerate() {
+                    let (num_first, num_second) =
+                        (first.matches(item).count(), second.matches(item).count());
+                    let moved = if in_first & (1 << idx) == 0 {
+                        num_first
+                    } else {
+                        first_len += num_first + num_second;
+                        num_second
+                    };
+                    cost.0 += moved;
+                    cost.1 += moved * to_priority(item).unwrap();
+                }
+                (first_len == first.len()).then_some(cost)
+            })
+            .min()
+    }
+
+    #[test]
+    fn example() {
+        for (plan, rucksack) in reorganize(EXAMPLE).into_iter().zip(EXAMPLE.lines()) {
+            let plan = plan.unwrap();
+            assert_eq!(
+                Some((plan.num_moves(), plan.priority_cost())),
+                brute_force(rucksack)
+            );
+        }
+    }
+
+    #[test]
+    fn cheapest_in_priorities() {
+        // moving `a` and `A` beats moving `b` and `A`, as well as `a` and `b`
+        let plan = plan(1, "aAbA").unwrap();
+        assert_eq!(
+            plan.moves,
+            vec![
+                Move {
+                    item: 'a',
+                    count: 1,
+                    from: Compartment::First,
+                    to: Compartment::Second
+                },
+                Move {
+                    item: 'A',
+                    count: 1,
+                    from: Compartment::Second,
+                    to: Compartment::First
+                },
+            ]
+        );
+        assert_eq!(plan.num_moves(), 2);
+        assert_eq!(plan.priority_cost(), 28);
+    }
+
+    #[test]
+    fn diagnostics() {
+        assert_eq!(
+            reorganize("aaab\nabcabc\nab\nabc\néa\n"),
+            vec![
+                Err(Diagnostic::NoBalancedSplit { line: 1 }),
+                Err(Diagnostic::NoBalancedSplit { line: 2 }),
+                Ok(Plan::default()),
+                Err(Diagnostic::OddLength { line: 4, len: 3 }),
+                Err(Diagnostic::InvalidItem {
+                    line: 5, item: 'é'
+                }),
+            ]
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn as_cheap_as_brute_force(rucksack in "([aAbBcd]{2}){0,7}") {
+            match plan(1, &rucksack) {
+                Ok(plan) => {
+                    prop_assert_eq!(Some((plan.num_moves(), plan.priority_cost())), brute_force(&rucksack));
+
+                    // after the moves, every item type is in one compartment, and both are the same size
+                    let (first, second) = rucksack.split_at(rucksack.len() / 2);
+                    let mut first_len = first.len();
+                    for m in &plan.moves {
+                        let from = if m.from == Compartment::First { first } else { second };
+                        prop_assert_eq!(m.count, from.matches(m.item).count());
+                        if m.from == Compartment::First {
+                            first_len -= m.count;
+                        } else {
+                            first_len += m.count;
+                        }
+                    }
+                    prop_assert_eq!(first_len, first.len());
+                    for item in rucksack.chars() {
+                        let moved = plan.moves.iter().any(|m| m.item == item);
+                        prop_assert!(moved || !(first.contains(item) && second.contains(item)));
+                    }
+                }
+                Err(diagnostic) => {
+                    prop_assert_eq!(diagnostic, Diagnostic::NoBalancedSplit { line: 1 });
+                    prop_assert_eq!(brute_force(&rucksack), None);
+                }
+            }
+        }
+    }
+}
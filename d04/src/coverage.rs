@@ -0,0 +1,302 @@
+use crate::Assignment;
+
+/// An elf, identified by the line of its pair (starting at 1) and its position in the pair (0 or 1)
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, PartialOrd, Ord)]
+pub struct Elf {
+    pub line: usize,
+    pub pair_idx: usize,
+}
+
+fn merge_adjacent(ranges: impl Iterator<Item = Assignment>) -> Vec<Assignment> {
+    let mut merged: Vec<Assignment> = Vec::new();
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) => match last.union_if_adjacent(range) {
+                Some(union) => *last = union,
+                None => merged.push(range),
+            },
+            None => merged.push(range),
+        }
+    }
+    merged
+}
+
+/// Coverage of the sections by all assignments in the camp
+pub struct Coverage {
+    /// sorted by the start of the assignment, to find those covering a section
+    assignments: Vec<(Assignment, Elf)>,
+    /// consecutive ranges of sections covered by the same elves, from the first covered section to the last one,
+    /// along with the number of those elves
+    segments: Vec<(Assignment, usize)>,
+    /// segment tree over `segments`: node `i` has children `2i` and `2i + 1`, and the leaf of the `n`-th segment
+    /// is node `segments.len() + n`. Each elf is stored at the nodes that together make up its segments,
+    /// so the elves covering a segment are those stored on the way from its leaf to the root
+    elves: Vec<Vec<Elf>>,
+}
+
+impl Coverage {
+    pub fn new(pairs: &[(Assignment, Assignment)]) -> Self {
+        let mut assignments: Vec<(Assignment, Elf)> = pairs
+            .iter()
+            .zip(1..)
+            .flat_map(|(&(elf1, elf2), line)| {
+                [
+                    (elf1, Elf { line, pair_idx: 0 }),
+                    (elf2, Elf { line, pair_idx: 1 }),
+                ]
+            })
+            .collect();
+        assignments.sort_unstable();
+
+        // an elf joins at the start of its assignment and leaves right after its end
+        let mut events: Vec<(u64, bool)> = assignments
+            .iter()
+            .flat_map(|&(assignment, _)| {
+                [
+                    (u64::from(assignment.start), true),
+                    (u64::from(assignment.end) + 1, false),
+                ]
+            })
+            .collect();
+        events.sort_unstable();
+
+        // sweep over the sections, cutting a new segment at each point where an elf joins or leaves
+        let mut segments = Vec::new();
+        let mut num_active = 0;
+        let mut events = events.into_iter().peekable();
+        while let Some((position, _)) = events.peek().copied() {
+            while let Some((_, joins)) = events.next_if(|&(p, _)| p == position) {
+                if joins {
+                    num_active += 1;
+                } else {
+                    num_active -= 1;
+                }
+            }
+            if let Some(&(next_position, _)) = events.peek() {
+                let segment = Assignment {
+                    start: position as u32,
+                    end: (next_position - 1) as u32,
+                };
+                segments.push((segment, num_active));
+            }
+        }
+
+        let mut coverage = Self {
+            assignments,
+            segments,
+            elves: Vec::new(),
+        };
+        coverage.elves = vec![Vec::new(); 2 * coverage.segments.len()];
+        for &(assignment, elf) in &coverage.assignments {
+            let (first, last) = coverage.segment_idxs_within(assignment);
+            let (mut left, mut right) = (
+                first + coverage.segments.len(),
+                last + coverage.segments.len(),
+            );
+            while left < right {
+                if left % 2 == 1 {
+                    coverage.elves[left].push(elf);
+                    left += 1;
+                }
+                if right % 2 == 1 {
+                    right -= 1;
+                    coverage.elves[right].push(elf);
+                }
+                left /= 2;
+                right /= 2;
+            }
+        }
+        coverage
+    }
+
+    /// The maximum number of elves assigned to the same section,
+    /// along with the ranges of sections where it's reached
+    pub fn max_overlap(&self) -> Option<(usize, Vec<Assignment>)> {
+        let max = self
+            .segments
+            .iter()
+            .map(|&(_, num_elves)| num_elves)
+            .max()?;
+        let ranges = self
+            .segments
+            .iter()
+            .filter(|&&(_, num_elves)| num_elves == max)
+            .map(|&(segment, _)| segment);
+        Some((max, merge_adjacent(ranges)))
+    }
+
+    /// Ranges of sections between the first and the last assigned one that no elf covers
+    pub fn uncovered(&self) -> Vec<Assignment> {
+        self.segments
+            .iter()
+            .filter(|&&(_, num_elves)| num_elves == 0)
+            .map(|&(segment, _)| segment)
+            .collect()
+    }
+
+    /// Indices of the segments overlapping `assignment` - since it starts and ends at segment borders,
+    /// exactly those covering it
+    fn segment_idxs_within(&self, assignment: Assignment) -> (usize, usize) {
+        let first = self
+            .segments
+            .partition_point(|(segment, _)| segment.end < assignment.start);
+        let last = self
+            .segments
+            .partition_point(|(segment, _)| segment.start <= assignment.end);
+        (first, last)
+    }
+
+    fn segments_within(&self, assignment: Assignment) -> &[(Assignment, usize)] {
+        let (first, last) = self.segment_idxs_within(assignment);
+        &self.segments[first..last]
+    }
+
+    /// Elves whose every section is also assigned to some other elf, in the order of their lines.
+    /// Note that removing one of them might make another one non-redundant
+    pub fn redundant(&self) -> Vec<Elf> {
+        let mut redundant: Vec<Elf> = self
+            .assignments
+            .iter()
+            .filter(|&&(assignment, _)| {
+                self.segments_within(assignment)
+                    .iter()
+                    .all(|&(_, num_elves)| num_elves >= 2)
+            })
+            .map(|&(_, elf)| elf)
+            .collect();
+        redundant.sort_unstable();
+        redundant
+    }
+
+    /// Elves that have `section` assigned to them, in the order of their lines.
+    /// Takes `O(log n + k)` for `n` assignments and `k` elves found, plus sorting those
+    pub fn covering(&self, section: u32) -> Vec<Elf> {
+        let segment_idx = self
+            .segments
+            .partition_point(|(segment, _)| segment.end < section);
+        if self
+            .segments
+            .get(segment_idx)
+            .is_none_or(|(segment, _)| section < segment.start)
+        {
+            return Vec::new();
+        }
+        let mut covering = Vec::new();
+        let mut node = self.segments.len() + segment_idx;
+        while node > 0 {
+            covering.extend_from_slice(&self.elves[node]);
+            node /= 2;
+        }
+        covering.sort_unstable();
+        covering
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assignment_pairs;
+    use proptest::{collection::vec, prelude::*};
+    const EXAMPLE: &str = include_str!("../inputs/example.txt");
+
+    fn elf(line: usize, pair_idx: usize) -> Elf {
+        Elf { line, pair_idx }
+    }
+
+    /// The elves assigned to each of the sections from the first assigned one to the last one
+    fn brute_force(pairs: &[(Assignment, Assignment)]) -> Vec<(u32, Vec<Elf>)> {
+        let elves: Vec<(Elf, Assignment)> = pairs
+            .iter()
+            .zip(1..)
+            .flat_map(|(&(elf1, elf2), line)| [(elf(line, 0), elf1), (elf(line, 1), elf2)])
+            .collect();
+        let Some(first) = elves.iter().map(|(_, a)| a.start()).min() else {
+            return Vec::new();
+        };
+        let last = elves.iter().map(|(_, a)| a.end()).max().unwrap();
+        (first..=last)
+            .map(|section| {
+                let covering = elves
+                    .iter()
+                    .filter(|(_, a)| a.sections().contains(&section))
+                    .map(|&(elf, _)| elf)
+                    .collect();
+                (section, covering)
+            })
+            .collect()
+    }
+
+    /// Merges the sections into ranges
+    fn ranges(sections: impl Iterator<Item = u32>) -> Vec<Assignment> {
+        merge_adjacent(sections.map(|section| Assignment::new(section, section).unwrap()))
+    }
+
+    #[test]
+    fn example() {
+        let coverage = Coverage::new(&assignment_pairs(EXAMPLE).unwrap());
+        assert_eq!(
+            coverage.max_overlap(),
+            Some((8, vec![Assignment::new(6, 6).unwrap()]))
+        );
+        assert_eq!(coverage.uncovered(), vec![]);
+        assert_eq!(coverage.covering(9), vec![elf(3, 1)]);
+        assert_eq!(coverage.covering(10), vec![]);
+        assert!(!coverage.redundant().contains(&elf(3, 1)));
+    }
+
+    #[test]
+    fn gaps() {
+        let coverage = Coverage::new(&assignment_pairs("1-2,5-6\n1-1,2-2\n").unwrap());
+        assert_eq!(coverage.uncovered(), vec![Assignment::new(3, 4).unwrap()]);
+        assert_eq!(coverage.redundant(), vec![elf(1, 0), elf(2, 0), elf(2, 1)]);
+        assert_eq!(
+            coverage.max_overlap(),
+            Some((2, vec![Assignment::new(1, 2).unwrap()]))
+        );
+    }
+
+    #[test]
+    fn empty() {
+        let coverage = Coverage::new(&[]);
+        assert_eq!(coverage.max_overlap(), None);
+        assert_eq!(coverage.uncovered(), vec![]);
+        assert_eq!(coverage.covering(0), vec![]);
+    }
+
+    fn assignment() -> impl Strategy<Value = Assignment> {
+        (0..20_u32, 0..6_u32).prop_map(|(start, len)| Assignment::new(start, start + len).unwrap())
+    }
+
+    proptest! {
+        #[test]
+        fn matches_brute_force(pairs in vec((assignment(), assignment()), 1..8)) {
+            let coverage = Coverage::new(&pairs);
+            let sections = brute_force(&pairs);
+
+            let max = sections.iter().map(|(_, elves)| elves.len()).max().unwrap();
+            let at_max = sections.iter().filter(|(_, elves)| elves.len() == max);
+            prop_assert_eq!(
+                coverage.max_overlap(),
+                Some((max, ranges(at_max.map(|&(section, _)| section))))
+            );
+
+            let uncovered = sections.iter().filter(|(_, elves)| elves.is_empty());
+            prop_assert_eq!(coverage.uncovered(), ranges(uncovered.map(|&(section, _)| section)));
+
+            for (section, elves) in &sections {
+                prop_assert_eq!(&coverage.covering(*section), elves);
+            }
+
+            let mut redundant: Vec<Elf> = sections.iter().flat_map(|(_, elves)| elves.iter().copied()).collect();
+            redundant.sort_unstable();
+            redundant.dedup();
+            redundant.retain(|elf| {
+                sections
+                    .iter()
+                    .filter(|(_, elves)| elves.contains(elf))
+                    .all(|(_, elves)| elves.len() >= 2)
+            });
+            prop_assert_eq!(coverage.redundant(), redundant);
+        }
+    }
+}
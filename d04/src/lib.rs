@@ -1,41 +1,177 @@
-use itertools::Itertools;
+use std::{ops::RangeInclusive, str::FromStr};
 
-pub fn p1(file: &str) -> usize {
-    // iterate over input lines
-    file.lines()
-        // parse each line as assignment pairs (represented by a 4-element tuple)
-        .map(|line| -> (u32, u32, u32, u32) {
-            line.split(&[',', '-'][..])
-                .map(|num| num.parse().unwrap())
-                .collect_tuple()
-                .expect("A well structured input")
+use anyhow::{ensure, Context};
+
+pub mod coverage;
+
+/// Range of sections assigned to an elf, including both ends
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, PartialOrd, Ord)]
+pub struct Assignment {
+    start: u32,
+    end: u32,
+}
+
+// an assignment always covers at least one section
+#[allow(clippy::len_without_is_empty)]
+impl Assignment {
+    pub fn new(start: u32, end: u32) -> anyhow::Result<Self> {
+        ensure!(
+            start <= end,
+            "assignment ends before it starts: {start}-{end}"
+        );
+        Ok(Self { start, end })
+    }
+
+    pub fn start(self) -> u32 {
+        self.start
+    }
+
+    pub fn end(self) -> u32 {
+        self.end
+    }
+
+    /// Number of sections - which doesn't fit into `u32` for the assignment covering all of them
+    pub fn len(self) -> u64 {
+        u64::from(self.end - self.start) + 1
+    }
+
+    pub fn sections(self) -> RangeInclusive<u32> {
+        self.start..=self.end
+    }
+
+    pub fn contains(self, other: Self) -> bool {
+        self.start <= other.start && other.end <= self.end
+    }
+
+    pub fn overlaps(self, other: Self) -> bool {
+        self.start <= other.end && other.start <= self.end
+    }
+
+    pub fn intersection(self, other: Self) -> Option<Self> {
+        self.overlaps(other).then(|| Self {
+            start: self.start.max(other.start),
+            end: self.end.min(other.end),
         })
-        // retain only the tuples where the exercise condition is met
-        .filter(|&(elf1_start, elf1_end, elf2_start, elf2_end)| {
-            (elf1_start <= elf2_start && elf2_end <= elf1_end)
-                || (elf2_start <= elf1_start && elf1_end <= elf2_end)
+    }
+
+    /// The assignment covering both, if together they form a single range without gaps
+    pub fn union_if_adjacent(self, other: Self) -> Option<Self> {
+        let adjacent =
+            self.start <= other.end.saturating_add(1) && other.start <= self.end.saturating_add(1);
+        adjacent.then(|| Self {
+            start: self.start.min(other.start),
+            end: self.end.max(other.end),
         })
-        // count such tuples
-        .count()
+    }
+}
+
+impl FromStr for Assignment {
+    type Err = anyhow::Error;
+
+    // 2-4
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (start, end) = s.split_once('-').context("expected '-'")?;
+        Self::new(
+            start
+                .parse()
+                .with_context(|| format!("invalid start: {start}"))?,
+            end.parse().with_context(|| format!("invalid end: {end}"))?,
+        )
+    }
 }
 
-pub fn p2(file: &str) -> usize {
-    // iterate over input lines
+/// Parses each line as a pair of assignments
+pub fn assignment_pairs(file: &str) -> anyhow::Result<Vec<(Assignment, Assignment)>> {
     file.lines()
-        // parse each line as assignment pairs (represented by a 4-element tuple)
-        .map(|line| -> (u32, u32, u32, u32) {
-            line.split(&[',', '-'][..])
-                .map(|num| num.parse().unwrap())
-                .collect_tuple()
-                .expect("A well structured input")
+        .zip(1..)
+        .map(|(line, line_number)| {
+            let (elf1, elf2) = line
+                .split_once(',')
+                .with_context(|| format!("line {line_number}: expected ','"))?;
+            let assignment =
+                |s| Assignment::from_str(s).with_context(|| format!("line {line_number}"));
+            Ok((assignment(elf1)?, assignment(elf2)?))
         })
-        // retain only the tuples where the exercise condition is met
-        .filter(|&(elf1_start, elf1_end, elf2_start, elf2_end)| {
-            (elf1_start <= elf2_start && elf2_start <= elf1_end)
-                || (elf1_start <= elf2_end && elf2_end <= elf1_end)
-                || (elf2_start <= elf1_start && elf1_start <= elf2_end)
-                || (elf2_start <= elf1_end && elf1_end <= elf2_end)
-        })
-        // count such tuples
-        .count()
+        .collect()
+}
+
+pub fn p1(file: &str) -> anyhow::Result<usize> {
+    Ok(assignment_pairs(file)?
+        .into_iter()
+        // retain only the pairs where one assignment fully contains the other
+        .filter(|&(elf1, elf2)| elf1.contains(elf2) || elf2.contains(elf1))
+        // count such pairs
+        .count())
+}
+
+pub fn p2(file: &str) -> anyhow::Result<usize> {
+    Ok(assignment_pairs(file)?
+        .into_iter()
+        // retain only the pairs where the assignments overlap at all
+        .filter(|&(elf1, elf2)| elf1.overlaps(elf2))
+        // count such pairs
+        .count())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+    const EXAMPLE: &str = include_str!("../inputs/example.txt");
+
+    fn assignment(s: &str) -> Assignment {
+        Assignment::from_str(s).unwrap()
+    }
+
+    #[test_case(EXAMPLE => 2; "example")]
+    fn test_p1(inp: &str) -> usize {
+        p1(inp).unwrap()
+    }
+
+    #[test_case(EXAMPLE => 4; "example")]
+    fn test_p2(inp: &str) -> usize {
+        p2(inp).unwrap()
+    }
+
+    #[test_case("2-4", "6-8" => (false, false, None); "apart")]
+    #[test_case("5-7", "7-9" => (false, true, Some(assignment("7-7"))); "sharing an end")]
+    #[test_case("2-8", "3-7" => (true, true, Some(assignment("3-7"))); "containing")]
+    #[test_case("6-6", "4-6" => (false, true, Some(assignment("6-6"))); "contained")]
+    fn relations(elf1: &str, elf2: &str) -> (bool, bool, Option<Assignment>) {
+        let (elf1, elf2) = (assignment(elf1), assignment(elf2));
+        let intersection = elf1.intersection(elf2);
+        assert_eq!(intersection, elf2.intersection(elf1));
+        (elf1.contains(elf2), elf1.overlaps(elf2), intersection)
+    }
+
+    #[test_case("2-3", "4-5" => Some(assignment("2-5")); "adjacent")]
+    #[test_case("2-4", "3-5" => Some(assignment("2-5")); "overlapping")]
+    #[test_case("2-3", "5-6" => None; "with a gap")]
+    #[test_case("0-0", "4294967295-4294967295" => None; "at the limits")]
+    fn union_if_adjacent(elf1: &str, elf2: &str) -> Option<Assignment> {
+        let (elf1, elf2) = (assignment(elf1), assignment(elf2));
+        assert_eq!(elf1.union_if_adjacent(elf2), elf2.union_if_adjacent(elf1));
+        elf1.union_if_adjacent(elf2)
+    }
+
+    #[test]
+    fn len() {
+        assert_eq!(assignment("6-6").len(), 1);
+        assert_eq!(assignment("2-8").len(), 7);
+        assert_eq!(assignment("0-4294967295").len(), 1 << 32);
+    }
+
+    #[test_case("3-1"; "reversed")]
+    #[test_case("1_3"; "no dash")]
+    #[test_case("1-x"; "not a number")]
+    fn invalid(s: &str) {
+        assert!(Assignment::from_str(s).is_err());
+    }
+
+    #[test]
+    fn line_numbers() {
+        let err = p1("2-4,6-8\n3-1,2-2").unwrap_err();
+        assert_eq!(err.to_string(), "line 2");
+        assert!(p2("2-4,6-8\n2-4;6-8").is_err());
+    }
 }
@@ -0,0 +1,149 @@
+use std::num::NonZeroUsize;
+
+use itertools::Itertools;
+
+/// How crates get from one stack to another
+pub trait Crane {
+    /// Moves `num_crates` crates from `from` to `to`.
+    /// Both stacks go bottom to top, and `from` holds at least `num_crates` crates
+    fn move_crates<T>(&self, from: &mut Vec<T>, to: &mut Vec<T>, num_crates: usize);
+
+    /// Reverts [`Crane::move_crates`], given the same stacks and number of crates.
+    /// The default works for any crane that takes crates from the top and puts them on the top
+    fn unmove_crates<T>(&self, from: &mut Vec<T>, to: &mut Vec<T>, num_crates: usize) {
+        unmove_from_top(self, from, to, num_crates);
+    }
+
+    /// Moves `num_crates` crates from `stack` onto itself, as in `move 2 from 1 to 1`.
+    /// The default works for any crane that takes crates from the top and puts them on the top
+    fn restack<T>(&self, stack: &mut Vec<T>, num_crates: usize) {
+        restack_on_top(self, stack, num_crates);
+    }
+
+    /// Reverts [`Crane::restack`], given the same stack and number of crates
+    fn unrestack<T>(&self, stack: &mut Vec<T>, num_crates: usize) {
+        unrestack_on_top(self, stack, num_crates);
+    }
+}
+
+fn unmove_from_top<T, C: Crane + ?Sized>(
+    crane: &C,
+    from: &mut Vec<T>,
+    to: &mut Vec<T>,
+    num_crates: usize,
+) {
+    // see where each of the lifted crates lands, by moving their positions instead
+    let mut positions = (0..num_crates).collect_vec();
+    let mut landed_positions = Vec::with_capacity(num_crates);
+    crane.move_crates(&mut positions, &mut landed_positions, num_crates);
+
+    // put each crate back into its original position
+    let mut lifted: Vec<Option<T>> = (0..num_crates).map(|_| None).collect();
+    for (crate_, position) in to.drain(to.len() - num_crates..).zip(landed_positions) {
+        lifted[position] = Some(crate_);
+    }
+    from.extend(lifted.into_iter().flatten());
+}
+
+/// The crates are put back on top in the order they'd land in on an empty stack
+fn restack_on_top<T, C: Crane + ?Sized>(crane: &C, stack: &mut Vec<T>, num_crates: usize) {
+    let mut lifted = Vec::with_capacity(num_crates);
+    crane.move_crates(stack, &mut lifted, num_crates);
+    stack.append(&mut lifted);
+}
+
+fn unrestack_on_top<T, C: Crane + ?Sized>(crane: &C, stack: &mut Vec<T>, num_crates: usize) {
+    let mut lifted = stack.split_off(stack.len() - num_crates);
+    crane.unmove_crates(stack, &mut lifted, num_crates);
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CraneModel {
+    /// lifts one crate at a time
+    CrateMover9000,
+    /// lifts all the crates at once
+    CrateMover9001,
+    /// lifts at most `capacity` crates at once
+    CapacityLimited { capacity: NonZeroUsize },
+    /// lifts at most `capacity` crates at once, but turns every other batch
+    /// (the first, the third, ...) upside down
+    AlternatingFlip { capacity: NonZeroUsize },
+    /// pulls all the crates at once from the bottom of a stack, and slides them under the other one
+    FromTheBottom,
+}
+
+/// Carries the crates from the top of one stack to the top of the other in batches of at most `capacity`
+fn move_in_batches<T>(
+    from: &mut Vec<T>,
+    to: &mut Vec<T>,
+    num_crates: usize,
+    capacity: NonZeroUsize,
+    flip_batch: impl Fn(usize) -> bool,
+) {
+    let mut left_to_move = num_crates;
+    for batch_idx in 0.. {
+        if left_to_move == 0 {
+            break;
+        }
+        let batch_size = left_to_move.min(capacity.get());
+        let batch = from.drain(from.len() - batch_size..);
+        if flip_batch(batch_idx) {
+            to.extend(batch.rev());
+        } else {
+            to.extend(batch);
+        }
+        left_to_move -= batch_size;
+    }
+}
+
+impl Crane for CraneModel {
+    fn move_crates<T>(&self, from: &mut Vec<T>, to: &mut Vec<T>, num_crates: usize) {
+        match self {
+            CraneModel::CrateMover9000 => to.extend(from.drain(from.len() - num_crates..).rev()),
+            CraneModel::CrateMover9001 => to.extend(from.drain(from.len() - num_crates..)),
+            CraneModel::CapacityLimited { capacity } => {
+                move_in_batches(from, to, num_crates, *capacity, |_| false);
+            }
+            CraneModel::AlternatingFlip { capacity } => {
+                move_in_batches(from, to, num_crates, *capacity, |batch_idx| {
+                    batch_idx % 2 == 0
+                });
+            }
+            CraneModel::FromTheBottom => {
+                let crates = from.drain(..num_crates).collect_vec();
+                to.splice(..0, crates);
+            }
+        }
+    }
+
+    fn unmove_crates<T>(&self, from: &mut Vec<T>, to: &mut Vec<T>, num_crates: usize) {
+        match self {
+            CraneModel::FromTheBottom => {
+                let crates = to.drain(..num_crates).collect_vec();
+                from.splice(..0, crates);
+            }
+            // the crane is its own inverse
+            CraneModel::CrateMover9000 | CraneModel::CrateMover9001 => {
+                self.move_crates(to, from, num_crates);
+            }
+            CraneModel::CapacityLimited { .. } | CraneModel::AlternatingFlip { .. } => {
+                unmove_from_top(self, from, to, num_crates);
+            }
+        }
+    }
+
+    fn restack<T>(&self, stack: &mut Vec<T>, num_crates: usize) {
+        match self {
+            // the crates slide back under the stack they came from
+            CraneModel::FromTheBottom => {}
+            _ => restack_on_top(self, stack, num_crates),
+        }
+    }
+
+    fn unrestack<T>(&self, stack: &mut Vec<T>, num_crates: usize) {
+        match self {
+            CraneModel::FromTheBottom => {}
+            _ => unrestack_on_top(self, stack, num_crates),
+        }
+    }
+}
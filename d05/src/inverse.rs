@@ -0,0 +1,216 @@
+use crate::{validate, Crane, Rearrangement, RearrangementError, Warehouse};
+
+/// The rearrangement that brings the crates back to where they were taken from
+fn reversed(rearrangement: &Rearrangement) -> Rearrangement {
+    Rearrangement {
+        num_crates_to_move: rearrangement.num_crates_to_move,
+        stack_to_move_to: rearrangement.stack_to_take_from,
+        stack_to_take_from: rearrangement.stack_to_move_to,
+    }
+}
+
+fn unapply_rearrangement<T, C: Crane>(
+    warehouse: &mut Warehouse<T>,
+    rearrangement: &Rearrangement,
+    crane: &C,
+) {
+    if rearrangement.stack_to_take_from == rearrangement.stack_to_move_to {
+        crane.unrestack(
+            &mut warehouse[rearrangement.stack_to_take_from],
+            rearrangement.num_crates_to_move,
+        );
+        return;
+    }
+    let [stack_taken_from, stack_moved_to] = warehouse
+        .get_disjoint_mut([
+            rearrangement.stack_to_take_from,
+            rearrangement.stack_to_move_to,
+        ])
+        .expect("two different existing stacks");
+
+    crane.unmove_crates(
+        stack_taken_from,
+        stack_moved_to,
+        rearrangement.num_crates_to_move,
+    );
+}
+
+/// Reconstructs the warehouse before the crane carried out the rearrangements, given the one after.
+/// The line numbers in the errors count the rearrangements, starting at 1
+pub fn initial_warehouse<T, C: Crane>(
+    final_warehouse: Warehouse<T>,
+    rearrangements: &[Rearrangement],
+    crane: &C,
+) -> Result<Warehouse<T>, RearrangementError> {
+    let mut warehouse = final_warehouse;
+    for (idx, rearrangement) in rearrangements.iter().enumerate().rev() {
+        validate(&warehouse, &reversed(rearrangement), idx + 1)?;
+        unapply_rearrangement(&mut warehouse, rearrangement, crane);
+    }
+    Ok(warehouse)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{apply_rearrangement, CraneModel};
+    use proptest::{collection::vec, prelude::*};
+    use std::num::NonZeroUsize;
+    use test_case::test_case;
+    const REAL: &str = include_str!("../inputs/real.txt");
+
+    fn forward<T, C: Crane>(
+        mut warehouse: Warehouse<T>,
+        rearrangements: &[Rearrangement],
+        crane: &C,
+    ) -> Warehouse<T> {
+        for rearrangement in rearrangements {
+            apply_rearrangement(&mut warehouse, rearrangement, crane);
+        }
+        warehouse
+    }
+
+    fn crane_model() -> impl Strategy<Value = CraneModel> {
+        prop_oneof![
+            Just(CraneModel::CrateMover9000),
+            Just(CraneModel::CrateMover9001),
+            (1..5usize).prop_map(|capacity| CraneModel::CapacityLimited {
+                capacity: NonZeroUsize::new(capacity).unwrap()
+            }),
+            (1..5usize).prop_map(|capacity| CraneModel::AlternatingFlip {
+                capacity: NonZeroUsize::new(capacity).unwrap()
+            }),
+            Just(CraneModel::FromTheBottom),
+        ]
+    }
+
+    /// A warehouse with distinct crates, and rearrangements that can be applied to it
+    fn warehouse_and_rearrangements() -> impl Strategy<Value = (Warehouse<u32>, Vec<Rearrangement>)>
+    {
+        (2..6usize)
+            .prop_flat_map(|num_stacks| {
+                (
+                    vec(0..8usize, num_stacks),
+                    vec((0..num_stacks, 0..num_stacks, 0..10usize), 0..40),
+                )
+            })
+            .prop_map(|(stack_sizes, raw_rearrangements)| {
+                let mut next_crate = 0..;
+                let warehouse: Warehouse<u32> = stack_sizes
+                    .iter()
+                    .map(|&size| next_crate.by_ref().take(size).collect())
+                    .collect();
+
+                // only ever move as many crates as there are
+                let mut stack_sizes = stack_sizes;
+                let rearrangements = raw_rearrangements
+                    .into_iter()
+                    .map(|(from, to, num_crates)| {
+                        let num_crates = num_crates.min(stack_sizes[from]);
+                        stack_sizes[from] -= num_crates;
+                        stack_sizes[to] += num_crates;
+                        Rearrangement {
+                            num_crates_to_move: num_crates,
+                            stack_to_move_to: to,
+                            stack_to_take_from: from,
+                        }
+                    })
+                    .collect();
+                (warehouse, rearrangements)
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn unrestack_reverts_restack(
+            stack in vec(0..100u32, 0..10),
+            num_crates in 0..10usize,
+            crane in crane_model(),
+        ) {
+            let num_crates = num_crates.min(stack.len());
+            let rearrangement = Rearrangement {
+                num_crates_to_move: num_crates,
+                stack_to_move_to: 0,
+                stack_to_take_from: 0,
+            };
+            let mut warehouse = vec![stack.clone()];
+            apply_rearrangement(&mut warehouse, &rearrangement, &crane);
+            unapply_rearrangement(&mut warehouse, &rearrangement, &crane);
+            prop_assert_eq!(warehouse, vec![stack]);
+        }
+
+        #[test]
+        fn forward_after_inverse_is_identity(
+            (initial, rearrangements) in warehouse_and_rearrangements(),
+            crane in crane_model(),
+        ) {
+            let final_warehouse = forward(initial, &rearrangements, &crane);
+            let reconstructed =
+                initial_warehouse(final_warehouse.clone(), &rearrangements, &crane).unwrap();
+            prop_assert_eq!(forward(reconstructed, &rearrangements, &crane), final_warehouse);
+        }
+
+        #[test]
+        fn inverse_recovers_initial(
+            (initial, rearrangements) in warehouse_and_rearrangements(),
+            crane in crane_model(),
+        ) {
+            let final_warehouse = forward(initial.clone(), &rearrangements, &crane);
+            prop_assert_eq!(
+                initial_warehouse(final_warehouse, &rearrangements, &crane).unwrap(),
+                initial
+            );
+        }
+    }
+
+    #[test_case(CraneModel::CrateMover9000; "9000")]
+    #[test_case(CraneModel::CrateMover9001; "9001")]
+    fn real(crane: CraneModel) {
+        let (initial, rearrangements) = crate::input(REAL).unwrap();
+        let final_warehouse = forward(initial.clone(), &rearrangements, &crane);
+        assert_eq!(
+            initial_warehouse(final_warehouse, &rearrangements, &crane).unwrap(),
+            initial
+        );
+    }
+
+    #[test_case(CraneModel::CrateMover9000 => vec!['a', 'b', 'c']; "9000")]
+    #[test_case(CraneModel::FromTheBottom => vec!['a', 'b', 'c']; "from the bottom")]
+    fn same_stack(crane: CraneModel) -> Vec<char> {
+        let rearrangements = [Rearrangement {
+            num_crates_to_move: 2,
+            stack_to_move_to: 0,
+            stack_to_take_from: 0,
+        }];
+        let final_warehouse = forward(vec![vec!['a', 'b', 'c']], &rearrangements, &crane);
+        initial_warehouse(final_warehouse, &rearrangements, &crane)
+            .unwrap()
+            .pop()
+            .unwrap()
+    }
+
+    #[test]
+    fn too_few_crates_to_take_back() {
+        let rearrangements = [Rearrangement {
+            num_crates_to_move: 2,
+            stack_to_move_to: 1,
+            stack_to_take_from: 0,
+        }];
+        let final_warehouse = vec![vec!['A'], vec!['B']];
+        assert_eq!(
+            initial_warehouse(
+                final_warehouse,
+                &rearrangements,
+                &CraneModel::CrateMover9000
+            ),
+            Err(RearrangementError::NotEnoughCrates {
+                line: 1,
+                requested: 2,
+                from: 2,
+                from_size: 1,
+                to: 1,
+                to_size: 1
+            })
+        );
+    }
+}
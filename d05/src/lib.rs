@@ -1,31 +1,33 @@
-use std::str::FromStr;
+use std::{fmt::Display, ops::Range, str::FromStr};
 
-use anyhow::Context;
-use libaoc::{impl_from_str_from_nom_parser, parse::n};
-use derive_deref::Deref;
+use anyhow::{anyhow, ensure, Context};
 use itertools::Itertools;
+use libaoc::{impl_from_str_from_nom_parser, parse::n};
 use nom::{
-    branch::alt,
-    bytes::complete::tag,
-    character::complete::{anychar, char},
-    combinator::map,
-    multi::separated_list0,
+    bytes::complete::{is_not, tag},
+    character::complete::char,
+    combinator::{map, map_opt},
     sequence::{delimited, preceded, tuple},
     IResult,
 };
 
-enum CraneModel {
-    CrateMover9000,
-    CrateMover9001,
-}
-struct Rearrangement {
-    num_crates_to_move: usize,
-    stack_to_move_to: usize,
-    stack_to_take_from: usize,
+pub mod crane;
+pub mod inverse;
+pub mod session;
+
+pub use crane::{Crane, CraneModel};
+
+/// Stack indices start at 0, unlike in the input
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Rearrangement {
+    pub num_crates_to_move: usize,
+    pub stack_to_move_to: usize,
+    pub stack_to_take_from: usize,
 }
 
+// stack numbers start at 1
 fn stack_idx(i: &str) -> IResult<&str, usize> {
-    map(n, |stack_idx: usize| stack_idx - 1)(i)
+    map_opt(n, |stack_number: usize| stack_number.checked_sub(1))(i)
 }
 
 fn num_crates(i: &str) -> IResult<&str, usize> {
@@ -50,133 +52,309 @@ fn rearrangement(i: &str) -> IResult<&str, Rearrangement> {
 
 impl_from_str_from_nom_parser!(rearrangement, Rearrangement);
 
-struct Crate {
-    name: char,
-}
-
-// [F]
-fn some_crate(i: &str) -> IResult<&str, Option<Crate>> {
-    map(delimited(char('['), anychar, char(']')), |name| {
-        Some(Crate { name })
-    })(i)
-}
+/// Range of columns in a line of the drawing
+type Columns = Range<usize>;
 
-fn no_crate(i: &str) -> IResult<&str, Option<Crate>> {
-    map(tag("   "), |_| None)(i)
+// [F], [AB]
+fn crate_name(i: &str) -> IResult<&str, &str> {
+    delimited(char('['), is_not("]"), char(']'))(i)
 }
 
-fn optional_crate(i: &str) -> IResult<&str, Option<Crate>> {
-    alt((some_crate, no_crate))(i)
+/// Crates drawn on a line, along with the columns each one takes up
+fn crate_line(line: &str) -> anyhow::Result<Vec<(Columns, String)>> {
+    let mut crates = Vec::new();
+    let mut rest = line.trim_start_matches(' ');
+    while !rest.is_empty() {
+        let start = line.len() - rest.len();
+        let (after, name) = crate_name(rest)
+            .map_err(|err| anyhow!("invalid crate at column {start} of {line:?}: {err}"))?;
+        crates.push((start..line.len() - after.len(), name.to_string()));
+        rest = after.trim_start_matches(' ');
+    }
+    Ok(crates)
 }
 
-#[derive(Deref)]
-struct CrateLine(Vec<Option<Crate>>);
-
-fn crate_line(i: &str) -> IResult<&str, CrateLine> {
-    map(separated_list0(char(' '), optional_crate), CrateLine)(i)
+/// Columns taken up by each stack number, which have to go 1, 2, 3, ...
+fn stack_numbers(footer: &str) -> anyhow::Result<Vec<Columns>> {
+    let mut stacks = Vec::new();
+    let mut rest = footer.trim_start_matches(' ');
+    while !rest.is_empty() {
+        let start = footer.len() - rest.len();
+        let (number, after) = rest.split_at(rest.find(' ').unwrap_or(rest.len()));
+        let stack_number: usize = number
+            .parse()
+            .with_context(|| format!("invalid stack number {number:?}"))?;
+        ensure!(
+            stack_number == stacks.len() + 1,
+            "expected stack number {}, found {stack_number}",
+            stacks.len() + 1
+        );
+        stacks.push(start..start + number.len());
+        rest = after.trim_start_matches(' ');
+    }
+    Ok(stacks)
 }
 
-impl_from_str_from_nom_parser!(crate_line, CrateLine);
+/// Stacks of crates, bottom to top
+pub type Warehouse<T = String> = Vec<Vec<T>>;
 
-type Warehouse = Vec<Vec<char>>;
-
-fn warehouse(s: &str) -> anyhow::Result<Warehouse> {
-    // remove the last row of the stack arrangement schema - the one with stack numbers
+/// Parses a drawing of the stacks. Each crate belongs to the stack whose number
+/// in the last row is below it, so neither crate names nor stack numbers need to be one character wide
+pub fn warehouse(s: &str) -> anyhow::Result<Warehouse> {
+    // the last row of the stack arrangement schema - the one with stack numbers - tells where the stacks are
     let (initial_stack_arrangement, last_row_of_stack_arrangement) =
         s.rsplit_once('\n').context("No stack numbers row")?;
-
-    // since we don't need the last row anyway, use it to indirectly calculate the number of stacks
-    let num_stacks = (last_row_of_stack_arrangement.len() + 1) / 4;
+    let stack_columns = stack_numbers(last_row_of_stack_arrangement)?;
 
     // initialize the warehouse (collection of stacks)
-    let mut stacks: Vec<Vec<char>> =
-        vec![Vec::with_capacity(initial_stack_arrangement.lines().count()); num_stacks];
+    let mut stacks: Warehouse =
+        vec![Vec::with_capacity(initial_stack_arrangement.lines().count()); stack_columns.len()];
 
     // parse the initial stack arrangement - fill up the warehouse
     // comment: go over lines bottom-up, since that's how the crates are stacked
     for line in initial_stack_arrangement.lines().rev() {
-        CrateLine::from_str(line)?
-            .iter()
-            // provide the stack number for each maybe-crate
-            .enumerate()
-            // if there's a crate, add it to the corresponding stack, skip if only air
-            .for_each(|(idx, optional_crate)| {
-                if let Some(some_crate) = optional_crate {
-                    stacks[idx].push(some_crate.name);
-                }
-            });
+        for (crate_columns, name) in crate_line(line)? {
+            // the crate goes onto the only stack whose number it's above
+            let stack_idx = stack_columns
+                .iter()
+                .positions(|columns| {
+                    columns.start < crate_columns.end && crate_columns.start < columns.end
+                })
+                .exactly_one()
+                .map_err(|_| {
+                    anyhow!("crate [{name}] in {line:?} isn't above exactly one stack number")
+                })?;
+            stacks[stack_idx].push(name);
+        }
     }
     Ok(stacks)
 }
 
-fn apply_rearrangement(
-    warehouse: &mut Warehouse,
+/// Renders a warehouse the way the puzzle input draws it.
+/// All columns are as wide as the widest crate or stack number, with the crates aligned to the right
+pub struct Drawing<'a, T = String>(pub &'a Warehouse<T>);
+
+impl<T: Display> Display for Drawing<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Drawing(stacks) = self;
+        let height = stacks.iter().map(Vec::len).max().unwrap_or(0);
+        let width = stacks
+            .iter()
+            .flatten()
+            .map(|name| name.to_string().len() + 2)
+            .chain([3, stacks.len().to_string().len()])
+            .max()
+            .unwrap_or(3);
+
+        // go over levels top-down, since that's how the drawing goes
+        for level in (0..height).rev() {
+            let line = stacks
+                .iter()
+                .map(|stack| match stack.get(level) {
+                    Some(name) => format!("{:>width$}", format!("[{name}]")),
+                    None => " ".repeat(width),
+                })
+                .join(" ");
+            writeln!(f, "{line}")?;
+        }
+
+        // the row with stack numbers, each one centered under its column
+        let stack_numbers = (1..=stacks.len())
+            .map(|stack_number| {
+                let number = stack_number.to_string();
+                let padding = width - number.len();
+                format!(
+                    "{}{number}{}",
+                    " ".repeat(padding - padding / 2),
+                    " ".repeat(padding / 2)
+                )
+            })
+            .join(" ");
+        write!(f, "{stack_numbers}")
+    }
+}
+
+fn apply_rearrangement<T, C: Crane>(
+    warehouse: &mut Warehouse<T>,
     rearrangement: &Rearrangement,
-    crane_model: &CraneModel,
+    crane: &C,
 ) {
-    let current_length_of_stack_to_move_from = warehouse[rearrangement.stack_to_take_from].len();
+    if rearrangement.stack_to_take_from == rearrangement.stack_to_move_to {
+        crane.restack(
+            &mut warehouse[rearrangement.stack_to_take_from],
+            rearrangement.num_crates_to_move,
+        );
+        return;
+    }
+    let [stack_to_take_from, stack_to_move_to] = warehouse
+        .get_disjoint_mut([
+            rearrangement.stack_to_take_from,
+            rearrangement.stack_to_move_to,
+        ])
+        .expect("two different existing stacks");
+
+    crane.move_crates(
+        stack_to_take_from,
+        stack_to_move_to,
+        rearrangement.num_crates_to_move,
+    );
+}
 
-    let crates_to_move = {
-        let crates = warehouse[rearrangement.stack_to_take_from]
-            .drain((current_length_of_stack_to_move_from - rearrangement.num_crates_to_move)..);
+/// Why a rearrangement can't be applied. Line and stack numbers are as in the input, i.e. start at 1
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum RearrangementError {
+    NoSuchStack {
+        line: usize,
+        stack: usize,
+        num_stacks: usize,
+    },
+    NotEnoughCrates {
+        line: usize,
+        requested: usize,
+        from: usize,
+        from_size: usize,
+        to: usize,
+        to_size: usize,
+    },
+}
 
-        match crane_model {
-            CraneModel::CrateMover9000 => crates.rev().collect_vec(),
-            CraneModel::CrateMover9001 => crates.collect(),
+impl Display for RearrangementError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RearrangementError::NoSuchStack {
+                line,
+                stack,
+                num_stacks,
+            } => write!(
+                f,
+                "line {line}: no stack {stack}, there are only {num_stacks}"
+            ),
+            RearrangementError::NotEnoughCrates {
+                line,
+                requested,
+                from,
+                from_size,
+                to,
+                to_size,
+            } => write!(
+                f,
+                "line {line}: can't move {requested} crates from stack {from} (size {from_size}) to stack {to} (size {to_size})"
+            ),
         }
-    };
+    }
+}
+
+impl std::error::Error for RearrangementError {}
 
-    warehouse[rearrangement.stack_to_move_to].extend(crates_to_move);
+/// Checks that `rearrangement`, found on line `line` of the input, can be applied to the warehouse
+pub fn validate<T>(
+    warehouse: &Warehouse<T>,
+    rearrangement: &Rearrangement,
+    line: usize,
+) -> Result<(), RearrangementError> {
+    let num_stacks = warehouse.len();
+    for stack in [
+        rearrangement.stack_to_take_from,
+        rearrangement.stack_to_move_to,
+    ] {
+        if stack >= num_stacks {
+            return Err(RearrangementError::NoSuchStack {
+                line,
+                stack: stack + 1,
+                num_stacks,
+            });
+        }
+    }
+    let from_size = warehouse[rearrangement.stack_to_take_from].len();
+    if rearrangement.num_crates_to_move > from_size {
+        return Err(RearrangementError::NotEnoughCrates {
+            line,
+            requested: rearrangement.num_crates_to_move,
+            from: rearrangement.stack_to_take_from + 1,
+            from_size,
+            to: rearrangement.stack_to_move_to + 1,
+            to_size: warehouse[rearrangement.stack_to_move_to].len(),
+        });
+    }
+    Ok(())
 }
 
-fn crates_at_the_top(warehouse: &Warehouse) -> anyhow::Result<String> {
+pub fn crates_at_the_top(warehouse: &Warehouse) -> anyhow::Result<String> {
     warehouse
         .iter()
-        .map(|stack| stack.last())
+        .map(|stack| stack.last().map(String::as_str))
         .collect::<Option<String>>()
         .context("One or more stack ended up empty")
 }
 
-pub fn p1(file: &str) -> anyhow::Result<String> {
-    let (initial_stack_schema, rearrangements) = file.split_once("\n\n").unwrap();
+fn split_input(file: &str) -> anyhow::Result<(&str, &str)> {
+    file.split_once("\n\n").context("No stack numbers row")
+}
 
-    let mut warehouse = warehouse(initial_stack_schema)?;
+/// Parses the initial stack arrangement and the rearrangements to apply to it
+pub fn input(file: &str) -> anyhow::Result<(Warehouse, Vec<Rearrangement>)> {
+    let (initial_stack_schema, rearrangements) = split_input(file)?;
 
-    // apply the rearrangements
-    for rearrangement in rearrangements.lines() {
-        let rearrangement = Rearrangement::from_str(rearrangement)?;
+    let warehouse = warehouse(initial_stack_schema)?;
+    let rearrangements = rearrangements
+        .lines()
+        .map(Rearrangement::from_str)
+        .collect::<anyhow::Result<_>>()?;
 
-        apply_rearrangement(&mut warehouse, &rearrangement, &CraneModel::CrateMover9000);
+    Ok((warehouse, rearrangements))
+}
+
+/// Crates at the top of each stack after the crane has carried out all the rearrangements
+pub fn rearrange(file: &str, crane: &impl Crane) -> anyhow::Result<String> {
+    let (mut warehouse, rearrangements) = input(file)?;
+
+    // apply the rearrangements
+    for rearrangement in &rearrangements {
+        apply_rearrangement(&mut warehouse, rearrangement, crane);
     }
 
     // get the final arrangement
     crates_at_the_top(&warehouse)
 }
 
-pub fn p2(file: &str) -> anyhow::Result<String> {
-    let (initial_stack_schema, rearrangements) =
-        file.split_once("\n\n").context("No stack numbers row")?;
-
-    let mut warehouse: Warehouse = warehouse(initial_stack_schema)?;
+/// Same as [`rearrange`], but checks each rearrangement before applying it
+pub fn rearrange_validated(file: &str, crane: &impl Crane) -> anyhow::Result<String> {
+    let (initial_stack_schema, rearrangements) = split_input(file)?;
+    let mut warehouse = warehouse(initial_stack_schema)?;
 
-    // apply the rearrangements
-    for rearrangement in rearrangements.lines() {
-        let rearrangement = Rearrangement::from_str(rearrangement)?;
+    // the rearrangements start after the drawing and the empty line
+    let first_line = initial_stack_schema.lines().count() + 2;
 
-        apply_rearrangement(&mut warehouse, &rearrangement, &CraneModel::CrateMover9001);
+    for (rearrangement, line) in rearrangements.lines().zip(first_line..) {
+        let rearrangement =
+            Rearrangement::from_str(rearrangement).with_context(|| format!("line {line}"))?;
+        validate(&warehouse, &rearrangement, line)?;
+        apply_rearrangement(&mut warehouse, &rearrangement, crane);
     }
 
-    // format the final arrangement
     crates_at_the_top(&warehouse)
 }
 
+pub fn p1(file: &str) -> anyhow::Result<String> {
+    rearrange(file, &CraneModel::CrateMover9000)
+}
+
+pub fn p2(file: &str) -> anyhow::Result<String> {
+    rearrange(file, &CraneModel::CrateMover9001)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::num::NonZeroUsize;
     use test_case::test_case;
     const EXAMPLE: &str = include_str!("../inputs/example.txt");
     const REAL: &str = include_str!("../inputs/real.txt");
 
+    fn capacity(capacity: usize) -> NonZeroUsize {
+        NonZeroUsize::new(capacity).unwrap()
+    }
+
     #[test_case(EXAMPLE => "CMZ"; "example")]
     #[test_case(REAL => "ZWHVFWQWW"; "real")]
     fn test_p1(inp: &str) -> String {
@@ -188,4 +366,194 @@ mod tests {
     fn test_p2(inp: &str) -> String {
         p2(inp).unwrap()
     }
+
+    #[test_case(EXAMPLE; "example")]
+    #[test_case(REAL; "real")]
+    fn capacity_limited_extremes(inp: &str) {
+        assert_eq!(
+            rearrange(
+                inp,
+                &CraneModel::CapacityLimited {
+                    capacity: capacity(1)
+                }
+            )
+            .unwrap(),
+            p1(inp).unwrap()
+        );
+        assert_eq!(
+            rearrange(
+                inp,
+                &CraneModel::CapacityLimited {
+                    capacity: capacity(100)
+                }
+            )
+            .unwrap(),
+            p2(inp).unwrap()
+        );
+        assert_eq!(
+            rearrange(
+                inp,
+                &CraneModel::AlternatingFlip {
+                    capacity: capacity(100)
+                }
+            )
+            .unwrap(),
+            p1(inp).unwrap()
+        );
+    }
+
+    #[test_case(CraneModel::CapacityLimited { capacity: capacity(2) } => vec!['d', 'e', 'b', 'c', 'a'])]
+    #[test_case(CraneModel::AlternatingFlip { capacity: capacity(2) } => vec!['e', 'd', 'b', 'c', 'a'])]
+    #[test_case(CraneModel::FromTheBottom => vec!['a', 'b', 'c', 'd', 'e'])]
+    fn move_five(crane: CraneModel) -> Vec<char> {
+        let mut from = vec!['a', 'b', 'c', 'd', 'e'];
+        let mut to = Vec::new();
+        crane.move_crates(&mut from, &mut to, 5);
+        to
+    }
+
+    #[test_case(CraneModel::CrateMover9000 => vec!['a', 'c', 'b'])]
+    #[test_case(CraneModel::CrateMover9001 => vec!['a', 'b', 'c'])]
+    #[test_case(CraneModel::CapacityLimited { capacity: capacity(1) } => vec!['a', 'c', 'b'])]
+    #[test_case(CraneModel::FromTheBottom => vec!['a', 'b', 'c'])]
+    fn move_two_onto_the_same_stack(crane: CraneModel) -> Vec<char> {
+        let mut warehouse = vec![vec!['a', 'b', 'c']];
+        let rearrangement = Rearrangement {
+            num_crates_to_move: 2,
+            stack_to_move_to: 0,
+            stack_to_take_from: 0,
+        };
+        apply_rearrangement(&mut warehouse, &rearrangement, &crane);
+        warehouse.pop().unwrap()
+    }
+
+    #[test]
+    fn same_stack_in_the_input() {
+        let inp = format!("{}\nmove 2 from 3 to 3", EXAMPLE.trim_end());
+        assert_eq!(p1(&inp).unwrap(), "CMN");
+        assert_eq!(p2(&inp).unwrap(), "MCD");
+    }
+
+    #[test_case(EXAMPLE; "example")]
+    #[test_case(REAL; "real")]
+    #[test_case(&format!("{}\nmove 2 from 3 to 3", EXAMPLE.trim_end()); "same stack")]
+    fn validated_matches_unvalidated(inp: &str) {
+        for crane in [CraneModel::CrateMover9000, CraneModel::CrateMover9001] {
+            assert_eq!(
+                rearrange_validated(inp, &crane).unwrap(),
+                rearrange(inp, &crane).unwrap()
+            );
+        }
+    }
+
+    #[test_case("move 3 from 2 to 1" => RearrangementError::NotEnoughCrates {
+        line: 8, requested: 3, from: 2, from_size: 2, to: 1, to_size: 0
+    }; "not enough crates")]
+    #[test_case("move 1 from 2 to 4" => RearrangementError::NoSuchStack {
+        line: 8, stack: 4, num_stacks: 3
+    }; "no such stack")]
+    #[test_case("move 5 from 3 to 3" => RearrangementError::NotEnoughCrates {
+        line: 8, requested: 5, from: 3, from_size: 4, to: 3, to_size: 4
+    }; "not enough crates on the same stack")]
+    fn invalid_rearrangement(third_move: &str) -> RearrangementError {
+        // replace the third move, before which stack 1 is empty and stack 2 holds 2 crates
+        let inp = EXAMPLE.replace("move 2 from 2 to 1", third_move);
+        rearrange_validated(&inp, &CraneModel::CrateMover9000)
+            .unwrap_err()
+            .downcast()
+            .unwrap()
+    }
+
+    #[test_case("move 1 from 0 to 1"; "stack 0")]
+    #[test_case("move 1 from 2 to two"; "not a number")]
+    fn unparsable_rearrangement(third_move: &str) {
+        let inp = EXAMPLE.replace("move 2 from 2 to 1", third_move);
+        let err = rearrange_validated(&inp, &CraneModel::CrateMover9000).unwrap_err();
+        assert_eq!(err.to_string(), "line 8");
+        assert!(rearrange(&inp, &CraneModel::CrateMover9000).is_err());
+    }
+
+    #[test_case(EXAMPLE; "example")]
+    #[test_case(REAL; "real")]
+    fn drawing_round_trip(inp: &str) {
+        let (drawing, _) = inp.split_once("\n\n").unwrap();
+        assert_eq!(Drawing(&warehouse(drawing).unwrap()).to_string(), drawing);
+    }
+
+    #[test]
+    fn intermediate_drawings_round_trip() {
+        let mut session =
+            session::CraneSession::from_input(REAL, CraneModel::CrateMover9001).unwrap();
+        while session.redo() {
+            let warehouse_then = session.warehouse();
+            let drawing = Drawing(&warehouse_then).to_string();
+            assert_eq!(warehouse(&drawing).unwrap(), warehouse_then);
+        }
+    }
+
+    const TEN_STACKS: &str = concat!(
+        "                                    [J]\n",
+        "[A] [B] [C] [D] [E] [F] [G] [H] [I] [K]\n",
+        " 1   2   3   4   5   6   7   8   9   10"
+    );
+    const WIDE_CRATES: &str = concat!("     [CD]     \n", "[AB] [EF]  [G]\n", "  1    2    3 ");
+
+    fn ten_stacks_warehouse() -> Warehouse {
+        "ABCDEFGHI"
+            .chars()
+            .map(|name| vec![name.to_string()])
+            .chain([vec!["K".to_string(), "J".to_string()]])
+            .collect()
+    }
+
+    fn wide_crates_warehouse() -> Warehouse {
+        vec![
+            vec!["AB".to_string()],
+            vec!["EF".to_string(), "CD".to_string()],
+            vec!["G".to_string()],
+        ]
+    }
+
+    #[test_case(TEN_STACKS => ten_stacks_warehouse(); "ten stacks")]
+    #[test_case(&TEN_STACKS.replace("9   10", "9  10 ") => ten_stacks_warehouse(); "ten stacks, number aligned left")]
+    #[test_case(WIDE_CRATES => wide_crates_warehouse(); "wide crates")]
+    #[test_case("     [CD]\n[AB] [EF] [G]\n 1    2    3" => wide_crates_warehouse(); "wide crates, aligned left")]
+    fn wide_drawings(drawing: &str) -> Warehouse {
+        warehouse(drawing).unwrap()
+    }
+
+    #[test_case(TEN_STACKS; "ten stacks")]
+    #[test_case(WIDE_CRATES; "wide crates")]
+    fn wide_drawings_round_trip(drawing: &str) {
+        assert_eq!(Drawing(&warehouse(drawing).unwrap()).to_string(), drawing);
+    }
+
+    #[test_case("[A]     [B]\n 1   2 "; "crate between stacks")]
+    #[test_case("[ABCDE]\n 1   2 "; "crate above two stacks")]
+    #[test_case("[A] [B]\n 1   3 "; "stack numbers out of order")]
+    #[test_case("[A] B\n 1   2 "; "crate without brackets")]
+    fn invalid_drawing(drawing: &str) {
+        assert!(warehouse(drawing).is_err());
+    }
+
+    #[test]
+    fn multi_digit_stack_numbers() {
+        let (warehouse, rearrangements) =
+            input(&format!("{TEN_STACKS}\n\nmove 1 from 10 to 1")).unwrap();
+        assert_eq!(
+            rearrangements,
+            vec![Rearrangement {
+                num_crates_to_move: 1,
+                stack_to_move_to: 0,
+                stack_to_take_from: 9,
+            }]
+        );
+        let mut warehouse = warehouse;
+        apply_rearrangement(
+            &mut warehouse,
+            &rearrangements[0],
+            &CraneModel::CrateMover9000,
+        );
+        assert_eq!(crates_at_the_top(&warehouse).unwrap(), "JBCDEFGHIK");
+    }
 }
@@ -0,0 +1,296 @@
+use anyhow::ensure;
+use itertools::Itertools;
+
+use crate::{
+    apply_rearrangement, crates_at_the_top, validate, Crane, CraneModel, Rearrangement,
+    RearrangementError, Warehouse,
+};
+
+/// Identifies a crate by where it stood in the initial arrangement
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub struct CrateId {
+    pub stack: usize,
+    /// 0 is the bottom of the stack
+    pub height: usize,
+}
+
+/// How many moves lie between two consecutive snapshots
+const SNAPSHOT_INTERVAL: usize = 64;
+
+/// Replays rearrangements one by one, keeping track of every single crate
+pub struct CraneSession<C = CraneModel> {
+    crane: C,
+    names: Warehouse,
+    moves: Vec<Rearrangement>,
+    /// how many of the recorded moves are currently applied
+    num_applied: usize,
+    /// the crates lifted by each of the moves applied so far (or before an undo)
+    lifted: Vec<Vec<CrateId>>,
+    current: Warehouse<CrateId>,
+    /// the ith snapshot is the state after `i * SNAPSHOT_INTERVAL` moves
+    snapshots: Vec<Warehouse<CrateId>>,
+}
+
+impl<C: Crane> CraneSession<C> {
+    pub fn new(initial: Warehouse, crane: C) -> Self {
+        let current: Warehouse<CrateId> = initial
+            .iter()
+            .enumerate()
+            .map(|(stack, crates)| {
+                (0..crates.len())
+                    .map(|height| CrateId { stack, height })
+                    .collect()
+            })
+            .collect();
+        Self {
+            crane,
+            names: initial,
+            moves: Vec::new(),
+            num_applied: 0,
+            lifted: Vec::new(),
+            snapshots: vec![current.clone()],
+            current,
+        }
+    }
+
+    /// A session with all the rearrangements from the input recorded, but none applied yet
+    pub fn from_input(file: &str, crane: C) -> anyhow::Result<Self> {
+        let (warehouse, rearrangements) = crate::input(file)?;
+        let mut session = Self::new(warehouse, crane);
+        session.moves = rearrangements;
+        Ok(session)
+    }
+
+    pub fn num_applied(&self) -> usize {
+        self.num_applied
+    }
+
+    pub fn num_recorded(&self) -> usize {
+        self.moves.len()
+    }
+
+    /// The moves applied so far
+    pub fn applied(&self) -> &[Rearrangement] {
+        &self.moves[..self.num_applied]
+    }
+
+    fn step(&mut self) {
+        if self.lifted.len() == self.num_applied {
+            let lifted = lifted(&self.current, &self.moves[self.num_applied], &self.crane);
+            self.lifted.push(lifted);
+        }
+        apply_rearrangement(
+            &mut self.current,
+            &self.moves[self.num_applied],
+            &self.crane,
+        );
+        self.num_applied += 1;
+        if self.num_applied.is_multiple_of(SNAPSHOT_INTERVAL)
+            && self.snapshots.len() == self.num_applied / SNAPSHOT_INTERVAL
+        {
+            self.snapshots.push(self.current.clone());
+        }
+    }
+
+    /// Applies a new move, discarding the moves that were undone before.
+    /// The line number in the error is the number of the move, starting at 1
+    pub fn apply(&mut self, rearrangement: Rearrangement) -> Result<(), RearrangementError> {
+        validate(&self.current, &rearrangement, self.num_applied + 1)?;
+        self.moves.truncate(self.num_applied);
+        self.lifted.truncate(self.num_applied);
+        self.snapshots
+            .truncate(self.num_applied / SNAPSHOT_INTERVAL + 1);
+        self.moves.push(rearrangement);
+        self.step();
+        Ok(())
+    }
+
+    /// Takes back the last applied move, if there is one
+    pub fn undo(&mut self) -> bool {
+        if self.num_applied == 0 {
+            return false;
+        }
+        self.jump_to(self.num_applied - 1).unwrap();
+        true
+    }
+
+    /// Applies the next recorded move, if there is one
+    pub fn redo(&mut self) -> bool {
+        if self.num_applied == self.moves.len() {
+            return false;
+        }
+        self.step();
+        true
+    }
+
+    /// Goes to the state after the first `num_moves` recorded moves
+    pub fn jump_to(&mut self, num_moves: usize) -> anyhow::Result<()> {
+        ensure!(
+            num_moves <= self.moves.len(),
+            "only {} moves recorded, can't jump to {num_moves}",
+            self.moves.len()
+        );
+        // replay from the closest snapshot, unless the current state is even closer
+        let snapshot_idx = (num_moves / SNAPSHOT_INTERVAL).min(self.snapshots.len() - 1);
+        let snapshot_num_moves = snapshot_idx * SNAPSHOT_INTERVAL;
+        if num_moves < self.num_applied || snapshot_num_moves > self.num_applied {
+            self.current.clone_from(&self.snapshots[snapshot_idx]);
+            self.num_applied = snapshot_num_moves;
+        }
+        while self.num_applied < num_moves {
+            self.step();
+        }
+        Ok(())
+    }
+
+    pub fn name(&self, crate_id: CrateId) -> &str {
+        &self.names[crate_id.stack][crate_id.height]
+    }
+
+    /// The current arrangement of the crates
+    pub fn warehouse(&self) -> Warehouse {
+        self.current
+            .iter()
+            .map(|stack| {
+                stack
+                    .iter()
+                    .map(|&crate_id| self.name(crate_id).to_string())
+                    .collect()
+            })
+            .collect()
+    }
+
+    pub fn crates_at_the_top(&self) -> anyhow::Result<String> {
+        crates_at_the_top(&self.warehouse())
+    }
+
+    /// Current stack and height of a crate
+    pub fn locate(&self, crate_id: CrateId) -> Option<(usize, usize)> {
+        locate(&self.current, crate_id)
+    }
+
+    /// Indices of the applied moves that have lifted the crate, starting at 0.
+    /// That includes moves putting it back onto the same stack
+    pub fn moves_touching(&self, crate_id: CrateId) -> Vec<usize> {
+        self.lifted[..self.num_applied]
+            .iter()
+            .positions(|lifted| lifted.contains(&crate_id))
+            .collect()
+    }
+}
+
+/// The crates the crane picks up to carry out `rearrangement`, i.e. the ones it'd put onto an empty stack
+fn lifted<C: Crane>(
+    warehouse: &Warehouse<CrateId>,
+    rearrangement: &Rearrangement,
+    crane: &C,
+) -> Vec<CrateId> {
+    let mut from = warehouse[rearrangement.stack_to_take_from].clone();
+    let mut lifted = Vec::with_capacity(rearrangement.num_crates_to_move);
+    crane.move_crates(&mut from, &mut lifted, rearrangement.num_crates_to_move);
+    lifted
+}
+
+fn locate(warehouse: &Warehouse<CrateId>, crate_id: CrateId) -> Option<(usize, usize)> {
+    warehouse.iter().enumerate().find_map(|(stack, crates)| {
+        crates
+            .iter()
+            .position(|&other| other == crate_id)
+            .map(|height| (stack, height))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+    const EXAMPLE: &str = include_str!("../inputs/example.txt");
+    const REAL: &str = include_str!("../inputs/real.txt");
+
+    #[test_case(EXAMPLE, CraneModel::CrateMover9000 => "CMZ"; "example 9000")]
+    #[test_case(EXAMPLE, CraneModel::CrateMover9001 => "MCD"; "example 9001")]
+    #[test_case(REAL, CraneModel::CrateMover9000 => "ZWHVFWQWW"; "real 9000")]
+    #[test_case(REAL, CraneModel::CrateMover9001 => "HZFZCCWWV"; "real 9001")]
+    fn replay(inp: &str, crane_model: CraneModel) -> String {
+        let mut session = CraneSession::from_input(inp, crane_model).unwrap();
+        while session.redo() {}
+        session.crates_at_the_top().unwrap()
+    }
+
+    #[test]
+    fn undo_and_jump_match_replay() {
+        let mut session = CraneSession::from_input(REAL, CraneModel::CrateMover9001).unwrap();
+        let states = (0..=session.num_recorded())
+            .map(|num_moves| {
+                session.jump_to(num_moves).unwrap();
+                session.warehouse()
+            })
+            .collect::<Vec<_>>();
+
+        while session.undo() {
+            assert_eq!(session.warehouse(), states[session.num_applied()]);
+        }
+        for num_moves in [300, 17, 128, 500, 64, 0, 499] {
+            session.jump_to(num_moves).unwrap();
+            assert_eq!(session.warehouse(), states[num_moves]);
+        }
+    }
+
+    #[test]
+    fn track_crate() {
+        let mut session = CraneSession::from_input(EXAMPLE, CraneModel::CrateMover9000).unwrap();
+        session.jump_to(session.num_recorded()).unwrap();
+        // D, initially on top of stack 2
+        let crate_id = CrateId {
+            stack: 1,
+            height: 2,
+        };
+        assert_eq!(session.name(crate_id), "D");
+        assert_eq!(session.locate(crate_id), Some((2, 1)));
+        assert_eq!(session.moves_touching(crate_id), vec![0, 1]);
+    }
+
+    #[test_case(CraneModel::CrateMover9000 => vec![vec![], vec![0, 1], vec![0]]; "9000")]
+    #[test_case(CraneModel::FromTheBottom => vec![vec![0, 1], vec![0], vec![]]; "from the bottom")]
+    fn moves_touching_the_same_stack(crane: CraneModel) -> Vec<Vec<usize>> {
+        let initial = vec![vec!["a".to_string(), "b".to_string(), "c".to_string()]];
+        let mut session = CraneSession::new(initial, crane);
+        for num_crates_to_move in [2, 1] {
+            let rearrangement = Rearrangement {
+                num_crates_to_move,
+                stack_to_move_to: 0,
+                stack_to_take_from: 0,
+            };
+            session.apply(rearrangement).unwrap();
+        }
+        (0..3)
+            .map(|height| session.moves_touching(CrateId { stack: 0, height }))
+            .collect()
+    }
+
+    #[test]
+    fn apply_invalid() {
+        let mut session = CraneSession::from_input(EXAMPLE, CraneModel::CrateMover9000).unwrap();
+        session.jump_to(2).unwrap();
+        // stack 1 is empty after the second move
+        let rearrangement = Rearrangement {
+            num_crates_to_move: 1,
+            stack_to_move_to: 1,
+            stack_to_take_from: 0,
+        };
+        assert_eq!(
+            session.apply(rearrangement),
+            Err(RearrangementError::NotEnoughCrates {
+                line: 3,
+                requested: 1,
+                from: 1,
+                from_size: 0,
+                to: 2,
+                to_size: 2
+            })
+        );
+        // nothing changes
+        assert_eq!(session.num_applied(), 2);
+        assert_eq!(session.num_recorded(), 4);
+    }
+}
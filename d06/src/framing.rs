@@ -0,0 +1,198 @@
+use std::{
+    io::{self, Read},
+    ops::Range,
+};
+
+use crate::{Marker, MarkerDetector};
+
+/// A part of the datastream that starts with a start-of-packet marker,
+/// and runs up to the next one or to the end of the stream
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Frame {
+    /// Position of the first byte of the marker in the datastream
+    pub offset: usize,
+    /// The marker followed by the payload
+    pub data: Vec<u8>,
+}
+
+impl Frame {
+    /// The data after the marker
+    pub fn payload(&self) -> &[u8] {
+        &self.data[Marker::StartOfPacket.size()..]
+    }
+}
+
+const CHUNK_SIZE: usize = 4096;
+
+/// Splits a datastream into frames while reading it.
+/// Whatever comes before the first marker doesn't belong to any frame, and is dropped
+pub struct Frames<R> {
+    reader: R,
+    detector: MarkerDetector,
+    chunk: Box<[u8]>,
+    /// the bytes of `chunk` that have been read, but not processed yet
+    unprocessed: Range<usize>,
+    /// bytes processed, but not yet handed out as part of a frame
+    pending: Vec<u8>,
+    /// position of the first pending byte in the datastream
+    pending_offset: usize,
+    /// whether the pending bytes start with a marker
+    in_frame: bool,
+    done: bool,
+}
+
+impl<R: Read> Frames<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            detector: MarkerDetector::new(Marker::StartOfPacket),
+            chunk: vec![0; CHUNK_SIZE].into_boxed_slice(),
+            unprocessed: 0..0,
+            pending: Vec::new(),
+            pending_offset: 0,
+            in_frame: false,
+            done: false,
+        }
+    }
+
+    /// Hands out the pending bytes up to `end` as a frame, if they form one
+    fn split_pending(&mut self, end: usize) -> Option<Frame> {
+        let data: Vec<u8> = self.pending.drain(..end - self.pending_offset).collect();
+        let offset = self.pending_offset;
+        self.pending_offset = end;
+        self.in_frame.then_some(Frame { offset, data })
+    }
+
+    /// Processes the next byte, returning the frame it ends
+    fn process(&mut self, byte: u8) -> Option<Frame> {
+        self.pending.push(byte);
+        if let Some(marker_end) = self.detector.push(byte) {
+            let frame = self.split_pending(marker_end - Marker::StartOfPacket.size());
+            self.in_frame = true;
+            return frame;
+        }
+
+        // outside of frames, only the bytes that might become part of a marker need to be kept
+        if !self.in_frame && self.pending.len() >= 2 * Marker::StartOfPacket.size() {
+            self.split_pending(self.detector.position() - Marker::StartOfPacket.size());
+        }
+        None
+    }
+}
+
+impl<R: Read> Iterator for Frames<R> {
+    type Item = io::Result<Frame>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(idx) = self.unprocessed.next() {
+                if let Some(frame) = self.process(self.chunk[idx]) {
+                    return Some(Ok(frame));
+                }
+                continue;
+            }
+            if self.done {
+                return None;
+            }
+            match self.reader.read(&mut self.chunk) {
+                Ok(0) => {
+                    // the last frame runs up to the end of the stream
+                    self.done = true;
+                    let end = self.detector.position();
+                    return self.split_pending(end).map(Ok);
+                }
+                Ok(num_read) => self.unprocessed = 0..num_read,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => {
+                    self.done = true;
+                    return Some(Err(err));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markers;
+    use test_case::test_case;
+    const EX1: &str = include_str!("../inputs/examples/1");
+    const REAL: &str = include_str!("../inputs/real.txt");
+
+    /// Hands out the data at most `chunk_size` bytes at a time
+    struct Chunked<'a> {
+        data: &'a [u8],
+        chunk_size: usize,
+    }
+
+    impl Read for Chunked<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let num_read = self.chunk_size.min(buf.len()).min(self.data.len());
+            let (chunk, rest) = self.data.split_at(num_read);
+            buf[..num_read].copy_from_slice(chunk);
+            self.data = rest;
+            Ok(num_read)
+        }
+    }
+
+    /// Frames cut at the markers found in the whole datastream at once
+    fn frames_at_markers(datastream: &[u8]) -> Vec<Frame> {
+        let starts: Vec<usize> = markers(datastream, Marker::StartOfPacket)
+            .map(|end| end - Marker::StartOfPacket.size())
+            .collect();
+        let ends = starts.iter().skip(1).copied().chain([datastream.len()]);
+        starts
+            .iter()
+            .zip(ends)
+            .map(|(&start, end)| Frame {
+                offset: start,
+                data: datastream[start..end].to_vec(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn frames() {
+        let frames: Vec<Frame> = Frames::new(&b"aabcdefabcdxyyx"[..])
+            .collect::<io::Result<_>>()
+            .unwrap();
+        assert_eq!(
+            frames,
+            vec![
+                Frame {
+                    offset: 1,
+                    data: b"abcd".to_vec()
+                },
+                Frame {
+                    offset: 5,
+                    data: b"efab".to_vec()
+                },
+                Frame {
+                    offset: 9,
+                    data: b"cdxyyx".to_vec()
+                },
+            ]
+        );
+        assert_eq!(frames[2].payload(), b"yx");
+    }
+
+    #[test_case(EX1; "example")]
+    #[test_case(REAL; "real")]
+    fn any_chunk_size(inp: &str) {
+        let expected = frames_at_markers(inp.as_bytes());
+        for chunk_size in (1..=20).chain([CHUNK_SIZE, usize::MAX]) {
+            let reader = Chunked {
+                data: inp.as_bytes(),
+                chunk_size,
+            };
+            let frames: Vec<Frame> = Frames::new(reader).collect::<io::Result<_>>().unwrap();
+            assert_eq!(frames, expected, "chunk size {chunk_size}");
+        }
+    }
+
+    #[test]
+    fn no_marker() {
+        assert_eq!(Frames::new(&b"abcabcabc"[..]).count(), 0);
+    }
+}
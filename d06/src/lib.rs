@@ -1,29 +1,100 @@
-use std::collections::VecDeque;
-
-/// Returns the index of the last element in the window,
-/// if such a window exists
-fn get_first_buffer_all_unique(string: &str, buffer_size: usize) -> Option<usize> {
-    let mut buf: VecDeque<char> = VecDeque::with_capacity(buffer_size);
-    let mut chars_to_skip: usize = 0;
-    for (idx, letter) in string.char_indices() {
-        while buf.contains(&letter) {
-            buf.pop_front();
-            chars_to_skip += 1;
+pub mod framing;
+
+/// The kinds of markers the device looks for
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Marker {
+    StartOfPacket,
+    StartOfMessage,
+}
+
+impl Marker {
+    /// Number of distinct bytes the marker consists of
+    pub const fn size(self) -> usize {
+        match self {
+            Marker::StartOfPacket => 4,
+            Marker::StartOfMessage => 14,
         }
-        buf.push_back(letter);
-        chars_to_skip = chars_to_skip.saturating_sub(1);
-        if buf.len() == buffer_size {
-            return Some(idx + 1);
+    }
+}
+
+/// Finds markers in a datastream fed to it one byte at a time
+#[derive(Clone, Debug)]
+pub struct MarkerDetector {
+    marker_size: usize,
+    /// number of bytes fed so far
+    position: usize,
+    /// for each byte, the position right after its last occurrence, 0 if there's none
+    next_after_last_seen: [usize; 256],
+    /// where the marker currently being looked at would start - no byte repeats between there and `position`
+    marker_start: usize,
+}
+
+impl MarkerDetector {
+    pub fn new(marker: Marker) -> Self {
+        Self::with_size(marker.size())
+    }
+
+    /// A detector for markers of `marker_size` distinct bytes
+    pub fn with_size(marker_size: usize) -> Self {
+        assert!(
+            marker_size > 0,
+            "a marker has to consist of at least one byte"
+        );
+        Self {
+            marker_size,
+            position: 0,
+            next_after_last_seen: [0; 256],
+            marker_start: 0,
         }
     }
-    None
+
+    /// Number of bytes fed so far
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Feeds the next byte, returning the number of bytes processed so far if it completes a marker.
+    /// The next marker may only start after the end of this one
+    pub fn push(&mut self, byte: u8) -> Option<usize> {
+        let last_seen = &mut self.next_after_last_seen[usize::from(byte)];
+        self.marker_start = self.marker_start.max(*last_seen);
+        self.position += 1;
+        *last_seen = self.position;
+
+        (self.position - self.marker_start == self.marker_size).then(|| {
+            self.marker_start = self.position;
+            self.position
+        })
+    }
+}
+
+/// Positions right after each marker in a datastream
+pub struct Markers<I> {
+    bytes: I,
+    detector: MarkerDetector,
+}
+
+impl<I: Iterator<Item = u8>> Iterator for Markers<I> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.bytes.find_map(|byte| self.detector.push(byte))
+    }
+}
+
+/// Iterates over every marker in the datastream, in O(1) per byte regardless of the marker size
+pub fn markers(datastream: &[u8], marker: Marker) -> Markers<impl Iterator<Item = u8> + '_> {
+    Markers {
+        bytes: datastream.iter().copied(),
+        detector: MarkerDetector::new(marker),
+    }
 }
 
 pub fn p1(buffer: &str) -> Option<usize> {
-    get_first_buffer_all_unique(buffer, 4)
+    markers(buffer.as_bytes(), Marker::StartOfPacket).next()
 }
 pub fn p2(buffer: &str) -> Option<usize> {
-    get_first_buffer_all_unique(buffer, 14)
+    markers(buffer.as_bytes(), Marker::StartOfMessage).next()
 }
 
 #[cfg(test)]
@@ -56,4 +127,45 @@ mod tests {
     fn test_p2(inp: &str) -> usize {
         p2(inp).unwrap()
     }
+
+    /// Markers found by checking every window from scratch
+    fn markers_naive(datastream: &[u8], marker_size: usize) -> Vec<usize> {
+        let mut markers = Vec::new();
+        let mut marker_start = 0;
+        for end in marker_size..=datastream.len() {
+            let start = end - marker_size;
+            let window = &datastream[start..end];
+            let all_unique = window
+                .iter()
+                .enumerate()
+                .all(|(idx, byte)| !window[idx + 1..].contains(byte));
+            if start >= marker_start && all_unique {
+                markers.push(end);
+                marker_start = end;
+            }
+        }
+        markers
+    }
+
+    #[test_case(EX1)]
+    #[test_case(EX2)]
+    #[test_case(EX3)]
+    #[test_case(EX4)]
+    #[test_case(EX5)]
+    #[test_case(REAL)]
+    fn all_markers(inp: &str) {
+        for marker in [Marker::StartOfPacket, Marker::StartOfMessage] {
+            assert_eq!(
+                markers(inp.as_bytes(), marker).collect::<Vec<_>>(),
+                markers_naive(inp.as_bytes(), marker.size())
+            );
+        }
+    }
+
+    #[test_case(b"abcdabcd" => vec![4, 8]; "back to back")]
+    #[test_case(b"abcabcdaaxyz" => vec![7, 12]; "after a repeat")]
+    #[test_case(b"aaaaaa" => Vec::<usize>::new(); "none")]
+    fn start_of_packet_markers(datastream: &[u8]) -> Vec<usize> {
+        markers(datastream, Marker::StartOfPacket).collect()
+    }
 }
@@ -0,0 +1,238 @@
+use std::path::{Path, PathBuf};
+
+use crate::tree::{Dir, Node};
+
+/// What a deletion plan should keep as small as possible
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Objective {
+    /// the total size of the deleted items, then their number
+    Bytes,
+    /// the number of deleted items, then their total size
+    Count,
+}
+
+impl Objective {
+    /// What to minimize, in order of importance
+    fn key(self, freed: u64, count: usize) -> (u64, u64) {
+        match self {
+            Objective::Bytes => (freed, count as u64),
+            Objective::Count => (count as u64, freed),
+        }
+    }
+}
+
+/// Files and directories to delete, none of them inside another
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Plan {
+    pub paths: Vec<PathBuf>,
+    pub freed: u64,
+}
+
+/// A file or directory, as part of the tree flattened in pre-order
+struct Item {
+    path: PathBuf,
+    size: u64,
+    /// index of the first item after this one's contents
+    end: usize,
+}
+
+/// Flattens the tree, putting bigger entries first so that good plans are found early
+fn flatten(dir: &Dir, path: &Path, items: &mut Vec<Item>) -> u64 {
+    let idx = items.len();
+    items.push(Item {
+        path: path.to_path_buf(),
+        size: 0,
+        end: 0,
+    });
+
+    let mut entries: Vec<(&String, &Node, u64)> = dir
+        .entries
+        .iter()
+        .map(|(name, node)| (name, node, node_size(node)))
+        .collect();
+    entries.sort_by_key(|&(_, _, size)| std::cmp::Reverse(size));
+
+    let mut size = 0;
+    for (name, node, node_size) in entries {
+        match node {
+            Node::Dir(subdir) => {
+                flatten(subdir, &path.join(name), items);
+            }
+            Node::File(_) => items.push(Item {
+                path: path.join(name),
+                size: node_size,
+                end: items.len() + 1,
+            }),
+        }
+        size += node_size;
+    }
+    items[idx].size = size;
+    items[idx].end = items.len();
+    size
+}
+
+fn node_size(node: &Node) -> u64 {
+    match node {
+        Node::Dir(dir) => dir.entries.values().map(node_size).sum(),
+        Node::File(file) => u64::from(file.size),
+    }
+}
+
+struct Search<'a> {
+    items: &'a [Item],
+    /// the most that can be freed by deleting items from the ith one on
+    freeable_from: Vec<u64>,
+    to_free: u64,
+    objective: Objective,
+    chosen: Vec<usize>,
+    best: Option<((u64, u64), Vec<usize>)>,
+}
+
+impl Search<'_> {
+    fn beats_best(&self, key: (u64, u64)) -> bool {
+        self.best.as_ref().is_none_or(|(best, _)| key < *best)
+    }
+
+    fn search(&mut self, idx: usize, freed: u64) {
+        if freed >= self.to_free {
+            let key = self.objective.key(freed, self.chosen.len());
+            if self.beats_best(key) {
+                self.best = Some((key, self.chosen.clone()));
+            }
+            return;
+        }
+        if idx == self.items.len() || freed + self.freeable_from[idx] < self.to_free {
+            return;
+        }
+        // at least one more item is needed, and the total is at least the target
+        if !self.beats_best(self.objective.key(self.to_free, self.chosen.len() + 1)) {
+            return;
+        }
+
+        let item = &self.items[idx];
+        if item.size > 0 {
+            let (end, size) = (item.end, item.size);
+            self.chosen.push(idx);
+            self.search(end, freed + size);
+            self.chosen.pop();
+        }
+        // keep the item, but maybe delete something inside it
+        self.search(idx + 1, freed);
+    }
+}
+
+/// Chooses what to delete to free at least `to_free` bytes, if possible at all.
+/// Explores every way to do so, skipping the ones that can't beat the best plan found so far,
+/// so it's exact, but only fast enough for trees of moderate size
+pub fn plan_deletion(root: &Dir, to_free: u64, objective: Objective) -> Option<Plan> {
+    let mut items = Vec::new();
+    flatten(root, Path::new("/"), &mut items);
+
+    let mut freeable_from = vec![0; items.len() + 1];
+    for idx in (0..items.len()).rev() {
+        freeable_from[idx] = items[idx].size + freeable_from[items[idx].end];
+    }
+
+    let mut search = Search {
+        items: &items,
+        freeable_from,
+        to_free,
+        objective,
+        chosen: Vec::new(),
+        best: None,
+    };
+    search.search(0, 0);
+
+    let (_, chosen) = search.best?;
+    let mut paths: Vec<PathBuf> = chosen.iter().map(|&idx| items[idx].path.clone()).collect();
+    paths.sort();
+    Some(Plan {
+        freed: chosen.iter().map(|&idx| items[idx].size).sum(),
+        paths,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{filesystem, left_to_free_up};
+    use test_case::test_case;
+    const EXAMPLE: &str = include_str!("../inputs/example.txt");
+    const REAL: &str = include_str!("../inputs/real.txt");
+
+    /// Every possible set of items to delete from the directory's contents, as (freed, count)
+    fn all_plans(dir: &Dir) -> Vec<(u64, usize)> {
+        dir.entries.values().fold(vec![(0, 0)], |plans, node| {
+            let mut node_plans = vec![(0, 0), (node_size(node), 1)];
+            if let Node::Dir(subdir) = node {
+                node_plans.extend(all_plans(subdir).into_iter().skip(1));
+            }
+            plans
+                .iter()
+                .flat_map(|&(freed, count)| {
+                    node_plans.iter().map(move |&(node_freed, node_count)| {
+                        (freed + node_freed, count + node_count)
+                    })
+                })
+                .collect()
+        })
+    }
+
+    fn best_plan_brute_force(
+        root: &Dir,
+        to_free: u64,
+        objective: Objective,
+    ) -> Option<(u64, usize)> {
+        all_plans(root)
+            .into_iter()
+            .chain([(node_size(&Node::Dir(root.clone())), 1)])
+            .filter(|&(freed, _)| freed >= to_free)
+            .min_by_key(|&(freed, count)| objective.key(freed, count))
+    }
+
+    #[test_case(1)]
+    #[test_case(584)]
+    #[test_case(100_000)]
+    #[test_case(8_381_165)]
+    #[test_case(20_000_000)]
+    #[test_case(30_000_000)]
+    #[test_case(48_381_165)]
+    #[test_case(48_381_166)]
+    fn matches_brute_force(to_free: u64) {
+        let root = filesystem(EXAMPLE).unwrap();
+        for objective in [Objective::Bytes, Objective::Count] {
+            let plan = plan_deletion(&root, to_free, objective);
+            assert_eq!(
+                plan.map(|plan| (plan.freed, plan.paths.len())),
+                best_plan_brute_force(&root, to_free, objective),
+                "{objective:?}"
+            );
+        }
+    }
+
+    #[test_case(Objective::Bytes => vec!["/a", "/b"])]
+    #[test_case(Objective::Count => vec!["/x"])]
+    fn objectives(objective: Objective) -> Vec<String> {
+        let root = filesystem("$ ls\n5 a\n7 b\ndir x\n$ cd x\n$ ls\n8 c\n9 d").unwrap();
+        plan_deletion(&root, 12, objective)
+            .unwrap()
+            .paths
+            .iter()
+            .map(|path| path.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn real() {
+        let root = filesystem(REAL).unwrap();
+        let to_free = u64::from(left_to_free_up(&root).unwrap());
+
+        // a single directory is enough, so the fewest deletions are exactly what p2 finds
+        let fewest = plan_deletion(&root, to_free, Objective::Count).unwrap();
+        assert_eq!(fewest.paths.len(), 1);
+        assert_eq!(fewest.freed, u64::from(crate::p2(REAL).unwrap()));
+
+        let smallest = plan_deletion(&root, to_free, Objective::Bytes).unwrap();
+        assert!((to_free..=fewest.freed).contains(&smallest.freed));
+    }
+}
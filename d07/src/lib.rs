@@ -1,88 +1,116 @@
 use std::{collections::HashMap, path::PathBuf};
 
-use anyhow::Context;
-use itertools::Itertools;
-
-type FilesWithSizes = HashMap<PathBuf, u32>;
-
-fn files_with_sizes(s: &str) -> anyhow::Result<FilesWithSizes> {
-    let mut current_path = PathBuf::new();
-    let mut files_with_sizes: FilesWithSizes = HashMap::new();
-
-    for input_and_output in s.split("\n$ ") {
-        if let Some(("cd", dir_name)) = input_and_output.split_once(' ') {
-            current_path = match dir_name {
-                ".." => current_path
-                    .parent()
-                    .map_or_else(PathBuf::new, std::path::Path::to_path_buf),
-                _ => current_path.join(dir_name),
-            }
-        } else if let Some(("ls", dir_contents)) = input_and_output.split_once('\n') {
-            let new_files = dir_contents
-                .lines()
-                .map(|line| line.split_once(' ').context("Invalid `ls` output"))
-                .collect::<Result<Vec<_>, _>>()?
-                .into_iter()
-                .filter_map(|(first, second)| match (first, second) {
-                    ("dir", _dir_name) => None,
-                    (file_size, file_name) => {
-                        let file_path = current_path.join(file_name);
-                        let file_size = file_size.parse().unwrap();
-                        Some((file_path, file_size))
+use anyhow::{bail, ensure, Context};
+
+pub mod cleanup;
+pub mod transcript;
+pub mod tree;
+
+use tree::{Dir, File, Node};
+
+pub type FilesWithSizes = HashMap<PathBuf, u32>;
+
+/// Builds the directory tree explored by the transcript.
+/// If the transcript doesn't start with `$ cd /`, the directory it starts in is taken as the root,
+/// and it's an error to leave it for its parent or to `cd /`, as it's unknown where they are
+pub fn filesystem(s: &str) -> anyhow::Result<Dir> {
+    let starts_at_root = s.lines().next() == Some("$ cd /");
+    let mut root = Dir::default();
+    // relative to the root
+    let mut current_path: Vec<String> = Vec::new();
+    // whether the lines that don't start with `$` are the output of `ls`
+    let mut listing = false;
+
+    for (line, line_number) in s.lines().zip(1..) {
+        let context = || format!("line {line_number}: {line}");
+        if let Some(command) = line.strip_prefix("$ ") {
+            listing = false;
+            match command.split_once(' ') {
+                Some(("cd", "/")) => {
+                    ensure!(
+                        starts_at_root,
+                        "`/` relative to the starting directory is unknown: {}",
+                        context()
+                    );
+                    current_path.clear();
+                }
+                Some(("cd", "..")) => {
+                    // like in a real shell, going up from the root stays there
+                    if current_path.pop().is_none() {
+                        ensure!(
+                            starts_at_root,
+                            "Going up from the starting directory: {}",
+                            context()
+                        );
                     }
-                });
-            files_with_sizes.extend(new_files);
+                }
+                Some(("cd", dir_name)) => {
+                    current_path.push(dir_name.to_string());
+                    // make sure it is a directory
+                    root.dir_mut(&current_path).with_context(context)?;
+                }
+                None if command == "ls" => {
+                    listing = true;
+                    root.dir_mut(&current_path)?.listed = true;
+                }
+                _ => bail!("Unknown command: {}", context()),
+            }
+        } else if listing {
+            let (first, name) = line
+                .split_once(' ')
+                .with_context(|| format!("Invalid `ls` output: {}", context()))?;
+            let node = match first {
+                "dir" => Node::Dir(Dir::default()),
+                file_size => Node::File(File {
+                    size: file_size
+                        .parse()
+                        .with_context(|| format!("Invalid file size: {}", context()))?,
+                }),
+            };
+            root.dir_mut(&current_path)?
+                .add(name, node)
+                .with_context(context)?;
+        } else if !line.is_empty() {
+            bail!("Output without `ls`: {}", context());
         }
     }
-    Ok(files_with_sizes)
-}
-
-type DirsWithSizes = HashMap<PathBuf, u32>;
-
-fn get_dir_sizes(files_with_sizes: &FilesWithSizes) -> DirsWithSizes {
-    files_with_sizes
-        .iter()
-        .flat_map(|(file_path, file_size)| {
-            file_path
-                .ancestors()
-                .skip(1)
-                .map(|ancestor_path| (ancestor_path.to_path_buf(), *file_size))
-        })
-        .into_grouping_map()
-        .sum()
+    Ok(root)
 }
 
 pub fn p1(file: &str) -> anyhow::Result<u32> {
     let upper_bound = 100_000u32;
 
-    let navigations = &file[2..];
-    let files_with_sizes = files_with_sizes(navigations)?;
-
-    let dirs_with_sizes = get_dir_sizes(&files_with_sizes);
+    let root = filesystem(file)?;
 
-    Ok(dirs_with_sizes
-        .into_values()
+    root.du()?
+        .into_iter()
+        .map(|(_, dir_size)| dir_size)
         .filter(|&dir_size| dir_size <= upper_bound)
-        .sum())
+        .try_fold(0u32, u32::checked_add)
+        .context("Sum of directory sizes overflows")
 }
 
-pub fn p2(file: &str) -> anyhow::Result<u32> {
-    let navigations = &file[2..];
-    let files_with_sizes = files_with_sizes(navigations)?;
-
-    let dirs_with_sizes = get_dir_sizes(&files_with_sizes);
-
+/// How much space has to be freed up for the update
+pub fn left_to_free_up(root: &Dir) -> anyhow::Result<u32> {
     let total_space = 70_000_000u32;
-    let total_used_space = *dirs_with_sizes.get(&PathBuf::from("/")).unwrap();
-    let total_available_space = total_space - total_used_space;
+    let total_used_space = root.size()?;
+    let total_available_space = total_space
+        .checked_sub(total_used_space)
+        .with_context(|| format!("{total_used_space} used, but the disk is only {total_space}"))?;
     let total_to_free_up = 30_000_000u32;
-    let left_to_free_up = total_to_free_up - total_available_space;
+    // if there's enough space already, any directory will do
+    Ok(total_to_free_up.saturating_sub(total_available_space))
+}
 
-    dirs_with_sizes
-        .into_values()
+pub fn p2(file: &str) -> anyhow::Result<u32> {
+    let root = filesystem(file)?;
+    let left_to_free_up = left_to_free_up(&root)?;
+
+    root.du()?
+        .into_iter()
+        .map(|(_, dir_size)| dir_size)
         .filter(|&dir_size| dir_size >= left_to_free_up)
-        .sorted_unstable()
-        .next()
+        .min()
         .context("At least one directory")
 }
 
@@ -103,4 +131,53 @@ mod tests {
     fn test_p2(inp: &str) -> u32 {
         p2(inp).unwrap()
     }
+
+    #[test]
+    fn not_starting_at_root() {
+        let inp = EXAMPLE.strip_prefix("$ cd /\n").unwrap();
+        assert_eq!(p1(inp).unwrap(), 95_437);
+        assert_eq!(p2(inp).unwrap(), 24_933_642);
+    }
+
+    #[test]
+    fn cd_root_and_repeated_ls() {
+        let inp = EXAMPLE.replace(
+            "$ cd ..\n$ cd ..\n$ cd d",
+            "$ cd /\n$ ls\n14848514 b.txt\ndir d\n$ cd a\n$ ls\n29116 f\n$ cd /\n$ cd d",
+        );
+        assert_eq!(filesystem(&inp).unwrap(), filesystem(EXAMPLE).unwrap());
+    }
+
+    #[test_case("$ cd /\n$ ls\n1 a\n$ ls\n2 a"; "conflicting sizes")]
+    #[test_case("$ cd /\n$ ls\n1 a\n$ ls\ndir a"; "file and dir")]
+    #[test_case("$ ls\n1 a\n$ cd a"; "cd into file")]
+    #[test_case("$ cd /\n1 a"; "output without ls")]
+    #[test_case("$ rm -rf /"; "unknown command")]
+    #[test_case("$ ls\n10 x\n$ cd ..\n$ ls\ndir a\n20 y"; "cd .. from the starting directory")]
+    #[test_case("$ ls\n10 x\n$ cd /\n$ ls\n20 y"; "cd / after starting elsewhere")]
+    #[test_case("$ ls\nbig a"; "invalid size")]
+    fn invalid_transcript(inp: &str) {
+        assert!(filesystem(inp).is_err());
+    }
+
+    #[test]
+    fn cd_up_from_root() {
+        let inp = "$ cd /\n$ cd ..\n$ ls\n10 x";
+        assert_eq!(
+            filesystem(inp).unwrap(),
+            filesystem("$ cd /\n$ ls\n10 x").unwrap()
+        );
+    }
+
+    #[test]
+    fn overflow() {
+        let inp = "$ ls\n4000000000 a\n4000000000 b";
+        assert!(p1(inp).is_err());
+        assert!(p2(inp).is_err());
+    }
+
+    #[test]
+    fn disk_over_full() {
+        assert!(p2("$ ls\n70000001 a").is_err());
+    }
 }
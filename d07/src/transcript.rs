@@ -0,0 +1,223 @@
+use crate::tree::{Dir, Node};
+
+/// One way to explore a directory and everything inside it, starting in the directory
+struct Exploration {
+    /// number of `cd`s
+    num_cds: usize,
+    /// depth of the directory the exploration ends in
+    end_depth: usize,
+    /// the subdirectory explored last, along with the way to explore it, unless the exploration comes back up
+    last: Option<(usize, usize)>,
+}
+
+/// The ways to explore a directory, where the subdirectories that need to be explored are
+/// those that have been listed or have something inside - the others are known from the parent's
+/// `ls` output, unless the parent hasn't been listed either and `cd` is the only way to find them
+struct Plan<'a> {
+    dir: &'a Dir,
+    depth: usize,
+    subdirs: Vec<(&'a str, Plan<'a>)>,
+    /// only those that aren't worse than another one in both `num_cds` and `end_depth`
+    explorations: Vec<Exploration>,
+    /// the way to explore the directory that takes the fewest `cd`s when coming back to the parent
+    best_round_trip: usize,
+}
+
+/// Number of `cd`s to get from a directory at `from_depth` to its ancestor at `to_depth`:
+/// either go up one by one, or jump to the root and go down from there
+fn num_cds_up(from_depth: usize, to_depth: usize) -> usize {
+    (from_depth - to_depth).min(1 + to_depth)
+}
+
+impl<'a> Plan<'a> {
+    fn new(dir: &'a Dir, depth: usize) -> Self {
+        let subdirs: Vec<(&str, Plan)> = dir
+            .entries
+            .iter()
+            .filter_map(|(name, node)| match node {
+                Node::Dir(subdir) if subdir.listed || !subdir.entries.is_empty() || !dir.listed => {
+                    Some((name.as_str(), Plan::new(subdir, depth + 1)))
+                }
+                _ => None,
+            })
+            .collect();
+
+        // going into a subdirectory, exploring it and coming back
+        let round_trip = |subdir: &Plan| 1 + subdir.round_trip_cds(subdir.best_round_trip, depth);
+        let all_round_trips: usize = subdirs.iter().map(|(_, subdir)| round_trip(subdir)).sum();
+
+        // either come back up after all the subdirectories, or stay in the one explored last
+        let mut explorations = vec![Exploration {
+            num_cds: all_round_trips,
+            end_depth: depth,
+            last: None,
+        }];
+        for (subdir_idx, (_, subdir)) in subdirs.iter().enumerate() {
+            for (exploration_idx, exploration) in subdir.explorations.iter().enumerate() {
+                explorations.push(Exploration {
+                    num_cds: all_round_trips - round_trip(subdir) + 1 + exploration.num_cds,
+                    end_depth: exploration.end_depth,
+                    last: Some((subdir_idx, exploration_idx)),
+                });
+            }
+        }
+
+        // only keep the explorations that take fewer `cd`s than any ending higher up
+        explorations.sort_by_key(|exploration| (exploration.end_depth, exploration.num_cds));
+        let mut fewest_cds = usize::MAX;
+        explorations.retain(|exploration| {
+            let keep = exploration.num_cds < fewest_cds;
+            fewest_cds = fewest_cds.min(exploration.num_cds);
+            keep
+        });
+
+        let mut plan = Self {
+            dir,
+            depth,
+            subdirs,
+            explorations,
+            best_round_trip: 0,
+        };
+        plan.best_round_trip = (0..plan.explorations.len())
+            .min_by_key(|&idx| plan.round_trip_cds(idx, depth.saturating_sub(1)))
+            .expect("coming back up is always possible");
+        plan
+    }
+
+    /// Number of `cd`s to take the `idx`th exploration and then go back up to `to_depth`
+    fn round_trip_cds(&self, idx: usize, to_depth: usize) -> usize {
+        let exploration = &self.explorations[idx];
+        exploration.num_cds + num_cds_up(exploration.end_depth, to_depth)
+    }
+
+    fn write_commands(&self, idx: usize, path: &mut Vec<&'a str>, commands: &mut Vec<String>) {
+        if self.dir.listed {
+            commands.push("$ ls".to_string());
+            for (name, node) in &self.dir.entries {
+                commands.push(match node {
+                    Node::Dir(_) => format!("dir {name}"),
+                    Node::File(file) => format!("{} {name}", file.size),
+                });
+            }
+        }
+
+        let last = self.explorations[idx].last;
+        for (subdir_idx, (name, subdir)) in self.subdirs.iter().enumerate() {
+            if last.is_some_and(|(last_idx, _)| last_idx == subdir_idx) {
+                continue;
+            }
+            commands.push(format!("$ cd {name}"));
+            path.push(name);
+            subdir.write_commands(subdir.best_round_trip, path, commands);
+            path.pop();
+
+            // come back
+            let end_depth = subdir.explorations[subdir.best_round_trip].end_depth;
+            if end_depth - self.depth <= 1 + self.depth {
+                commands.extend((self.depth..end_depth).map(|_| "$ cd ..".to_string()));
+            } else {
+                commands.push("$ cd /".to_string());
+                commands.extend(path.iter().map(|name| format!("$ cd {name}")));
+            }
+        }
+
+        if let Some((subdir_idx, exploration_idx)) = last {
+            let (name, subdir) = &self.subdirs[subdir_idx];
+            commands.push(format!("$ cd {name}"));
+            path.push(name);
+            subdir.write_commands(exploration_idx, path, commands);
+            path.pop();
+        }
+    }
+}
+
+/// A terminal session that explores the whole tree, starting with `cd /` like the puzzle's do,
+/// with as few commands as possible: one `ls` per listed directory, and the fewest `cd`s to get there
+pub fn transcript(root: &Dir) -> String {
+    let plan = Plan::new(root, 0);
+    let fewest_cds = (0..plan.explorations.len())
+        .min_by_key(|&idx| plan.explorations[idx].num_cds)
+        .expect("at least one way to explore");
+
+    let mut commands = vec!["$ cd /".to_string()];
+    plan.write_commands(fewest_cds, &mut Vec::new(), &mut commands);
+    commands.into_iter().map(|line| line + "\n").collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filesystem;
+    use test_case::test_case;
+    const EXAMPLE: &str = include_str!("../inputs/example.txt");
+    const REAL: &str = include_str!("../inputs/real.txt");
+
+    fn num_commands(transcript: &str) -> usize {
+        transcript
+            .lines()
+            .filter(|line| line.starts_with("$ "))
+            .count()
+    }
+
+    #[test_case(EXAMPLE; "example")]
+    #[test_case(REAL; "real")]
+    #[test_case("$ cd /\n$ ls\ndir a\ndir b\n$ cd b\n$ ls\n$ cd ..\n$ ls\n$ cd a\n$ cd c"; "partly explored")]
+    fn round_trip(inp: &str) {
+        let root = filesystem(inp).unwrap();
+        let synthesized = transcript(&root);
+        assert!(synthesized.starts_with("$ cd /\n"));
+        assert_eq!(filesystem(&synthesized).unwrap(), root);
+        assert!(num_commands(&synthesized) < num_commands(inp));
+    }
+
+    #[test_case(EXAMPLE; "example")]
+    #[test_case(REAL; "real")]
+    fn from_files(inp: &str) {
+        let root = filesystem(inp).unwrap();
+        let from_files = Dir::from_files(&root.files()).unwrap();
+        assert_eq!(
+            filesystem(&transcript(&from_files)).unwrap().du().unwrap(),
+            root.du().unwrap()
+        );
+    }
+
+    #[test]
+    fn example() {
+        let expected = "\
+$ cd /
+$ ls
+dir a
+14848514 b.txt
+8504156 c.dat
+dir d
+$ cd a
+$ ls
+dir e
+29116 f
+2557 g
+62596 h.lst
+$ cd e
+$ ls
+584 i
+$ cd /
+$ cd d
+$ ls
+5626152 d.ext
+8033020 d.log
+4060174 j
+7214296 k
+";
+        assert_eq!(transcript(&filesystem(EXAMPLE).unwrap()), expected);
+    }
+
+    #[test]
+    fn deep_branch_then_shallow_one() {
+        // whichever branch goes first, a single `cd /` gets back from its end
+        let inp = "$ cd /\n$ ls\ndir a\ndir e\n$ cd a\n$ ls\ndir b\n$ cd b\n$ ls\ndir c\n$ cd c\n$ ls\ndir d\n$ cd d\n$ ls\n1 x\n$ cd /\n$ cd e\n$ ls\n1 y\ndir f\n$ cd f\n$ ls\n1 z\n";
+        let root = filesystem(inp).unwrap();
+        let synthesized = transcript(&root);
+        assert_eq!(filesystem(&synthesized).unwrap(), root);
+        // `cd /` to start with, 7 `ls`, 6 `cd`s into the directories, and one back
+        assert_eq!(num_commands(&synthesized), 15);
+    }
+}
@@ -0,0 +1,329 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    ops::RangeBounds,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, ensure, Context};
+
+use crate::FilesWithSizes;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct File {
+    pub size: u32,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Dir {
+    /// the contents of the directory, by name
+    pub entries: BTreeMap<String, Node>,
+    /// whether the directory has been listed, i.e. whether `entries` are all there is
+    pub listed: bool,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Node {
+    Dir(Dir),
+    File(File),
+}
+
+/// Whether `name` matches a shell-like pattern, where `*` stands for any sequence of characters and `?` for any single one
+fn glob_match(pattern: &[char], name: &[char]) -> bool {
+    match (pattern.split_first(), name.split_first()) {
+        (None, _) => name.is_empty(),
+        (Some(('*', rest)), _) => {
+            glob_match(rest, name) || (!name.is_empty() && glob_match(pattern, &name[1..]))
+        }
+        (Some(('?', rest)), Some((_, name_rest))) => glob_match(rest, name_rest),
+        (Some((p, rest)), Some((c, name_rest))) => p == c && glob_match(rest, name_rest),
+        (Some(_), None) => false,
+    }
+}
+
+impl Dir {
+    /// The tree holding exactly the given files, whose paths have to be absolute
+    pub fn from_files(files: &FilesWithSizes) -> anyhow::Result<Self> {
+        let mut root = Dir::default();
+        for (path, &size) in files {
+            let mut components: Vec<String> = path
+                .strip_prefix("/")
+                .with_context(|| format!("Not an absolute path: {}", path.display()))?
+                .iter()
+                .map(|component| component.to_string_lossy().into_owned())
+                .collect();
+            let name = components.pop().context("The root isn't a file")?;
+            root.dir_mut(&components)?
+                .add(&name, Node::File(File { size }))?;
+        }
+        root.mark_listed();
+        Ok(root)
+    }
+
+    fn mark_listed(&mut self) {
+        self.listed = true;
+        for node in self.entries.values_mut() {
+            if let Node::Dir(subdir) = node {
+                subdir.mark_listed();
+            }
+        }
+    }
+
+    /// The directory at `path` (relative to this one), creating the missing directories on the way
+    pub(crate) fn dir_mut(&mut self, path: &[String]) -> anyhow::Result<&mut Dir> {
+        let mut dir = self;
+        for name in path {
+            dir = match dir
+                .entries
+                .entry(name.clone())
+                .or_insert_with(|| Node::Dir(Dir::default()))
+            {
+                Node::Dir(subdir) => subdir,
+                Node::File(_) => bail!("{name} is a file, not a directory"),
+            };
+        }
+        Ok(dir)
+    }
+
+    /// Adds an entry reported by `ls`, checking that it agrees with what was reported before
+    pub(crate) fn add(&mut self, name: &str, node: Node) -> anyhow::Result<()> {
+        match (self.entries.get(name), &node) {
+            (None, _) => {
+                self.entries.insert(name.to_string(), node);
+            }
+            // keep whatever has been found out about the directory so far
+            (Some(Node::Dir(_)), Node::Dir(_)) => {}
+            (Some(Node::File(old)), Node::File(new)) => ensure!(
+                old == new,
+                "{name} listed with two different sizes: {} and {}",
+                old.size,
+                new.size
+            ),
+            (Some(_), _) => bail!("{name} listed both as a file and as a directory"),
+        }
+        Ok(())
+    }
+
+    /// Every file and directory in the tree along with its path, parents before their contents
+    pub fn walk(&self) -> Vec<(PathBuf, &Node)> {
+        fn walk_into<'a>(dir: &'a Dir, path: &Path, entries: &mut Vec<(PathBuf, &'a Node)>) {
+            for (name, node) in &dir.entries {
+                let node_path = path.join(name);
+                entries.push((node_path.clone(), node));
+                if let Node::Dir(subdir) = node {
+                    walk_into(subdir, &node_path, entries);
+                }
+            }
+        }
+
+        let mut entries = Vec::new();
+        walk_into(self, Path::new("/"), &mut entries);
+        entries
+    }
+
+    /// Total size of the files in the directory and all of its subdirectories
+    pub fn size(&self) -> anyhow::Result<u32> {
+        self.entries.values().try_fold(0u32, |size, node| {
+            let node_size = match node {
+                Node::Dir(subdir) => subdir.size()?,
+                Node::File(file) => file.size,
+            };
+            size.checked_add(node_size)
+                .context("Directory size overflow")
+        })
+    }
+
+    /// Like `du`: every directory in the tree, including this one as `/`, with its total size.
+    /// Subdirectories come before their parents
+    pub fn du(&self) -> anyhow::Result<Vec<(PathBuf, u32)>> {
+        fn du_into(
+            dir: &Dir,
+            path: PathBuf,
+            sizes: &mut Vec<(PathBuf, u32)>,
+        ) -> anyhow::Result<u32> {
+            let mut size = 0u32;
+            for (name, node) in &dir.entries {
+                let node_size = match node {
+                    Node::Dir(subdir) => du_into(subdir, path.join(name), sizes)?,
+                    Node::File(file) => file.size,
+                };
+                size = size
+                    .checked_add(node_size)
+                    .with_context(|| format!("Size of {} overflows", path.display()))?;
+            }
+            sizes.push((path, size));
+            Ok(size)
+        }
+
+        let mut sizes = Vec::new();
+        du_into(self, PathBuf::from("/"), &mut sizes)?;
+        Ok(sizes)
+    }
+
+    /// Like `find -name`: paths of the files and directories whose names match `pattern`
+    pub fn find_by_name(&self, pattern: &str) -> Vec<PathBuf> {
+        let pattern = pattern.chars().collect::<Vec<_>>();
+        self.walk()
+            .into_iter()
+            .map(|(path, _)| path)
+            .filter(|path| {
+                let name = path.file_name().unwrap_or_default().to_string_lossy();
+                glob_match(&pattern, &name.chars().collect::<Vec<_>>())
+            })
+            .collect()
+    }
+
+    /// Like `find -type f -size`: files with a size in `sizes`
+    pub fn find_by_size(&self, sizes: impl RangeBounds<u32>) -> Vec<(PathBuf, u32)> {
+        self.walk()
+            .into_iter()
+            .filter_map(|(path, node)| match node {
+                Node::File(file) if sizes.contains(&file.size) => Some((path, file.size)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Directories that have been listed, without any files or subdirectories
+    pub fn empty_dirs(&self) -> Vec<PathBuf> {
+        self.walk()
+            .into_iter()
+            .filter_map(|(path, node)| match node {
+                Node::Dir(dir) if dir.listed && dir.entries.is_empty() => Some(path),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Directories that have never been listed, whose contents are unknown
+    pub fn unexplored_dirs(&self) -> Vec<PathBuf> {
+        self.walk()
+            .into_iter()
+            .filter_map(|(path, node)| match node {
+                Node::Dir(dir) if !dir.listed => Some(path),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Every file in the tree, by path
+    pub fn files(&self) -> FilesWithSizes {
+        self.walk()
+            .into_iter()
+            .filter_map(|(path, node)| match node {
+                Node::File(file) => Some((path, file.size)),
+                Node::Dir(_) => None,
+            })
+            .collect()
+    }
+}
+
+/// Renders the tree the way the puzzle does, with entries sorted by name
+impl Display for Dir {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fn fmt_contents(
+            dir: &Dir,
+            depth: usize,
+            f: &mut std::fmt::Formatter<'_>,
+        ) -> std::fmt::Result {
+            for (name, node) in &dir.entries {
+                let indent = "  ".repeat(depth);
+                match node {
+                    Node::Dir(subdir) => {
+                        writeln!(f, "{indent}- {name} (dir)")?;
+                        fmt_contents(subdir, depth + 1, f)?;
+                    }
+                    Node::File(file) => writeln!(f, "{indent}- {name} (file, size={})", file.size)?,
+                }
+            }
+            Ok(())
+        }
+
+        writeln!(f, "- / (dir)")?;
+        fmt_contents(self, 1, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filesystem;
+    use test_case::test_case;
+    const EXAMPLE: &str = include_str!("../inputs/example.txt");
+
+    #[test]
+    fn render() {
+        let expected = "\
+- / (dir)
+  - a (dir)
+    - e (dir)
+      - i (file, size=584)
+    - f (file, size=29116)
+    - g (file, size=2557)
+    - h.lst (file, size=62596)
+  - b.txt (file, size=14848514)
+  - c.dat (file, size=8504156)
+  - d (dir)
+    - d.ext (file, size=5626152)
+    - d.log (file, size=8033020)
+    - j (file, size=4060174)
+    - k (file, size=7214296)
+";
+        assert_eq!(filesystem(EXAMPLE).unwrap().to_string(), expected);
+    }
+
+    #[test]
+    fn du() {
+        let root = filesystem(EXAMPLE).unwrap();
+        assert_eq!(
+            root.du().unwrap(),
+            vec![
+                (PathBuf::from("/a/e"), 584),
+                (PathBuf::from("/a"), 94_853),
+                (PathBuf::from("/d"), 24_933_642),
+                (PathBuf::from("/"), 48_381_165),
+            ]
+        );
+        assert_eq!(root.size().unwrap(), 48_381_165);
+    }
+
+    #[test_case("*.*" => vec!["/a/h.lst", "/b.txt", "/c.dat", "/d/d.ext", "/d/d.log"]; "with extension")]
+    #[test_case("d*" => vec!["/d", "/d/d.ext", "/d/d.log"]; "prefix")]
+    #[test_case("?" => vec!["/a", "/a/e", "/a/e/i", "/a/f", "/a/g", "/d", "/d/j", "/d/k"]; "single character")]
+    #[test_case("*.l?g" => vec!["/d/d.log"]; "mixed")]
+    #[test_case("x*" => Vec::<&str>::new(); "none")]
+    fn find_by_name(pattern: &str) -> Vec<String> {
+        filesystem(EXAMPLE)
+            .unwrap()
+            .find_by_name(pattern)
+            .into_iter()
+            .map(|path| path.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn find_by_size() {
+        assert_eq!(
+            filesystem(EXAMPLE).unwrap().find_by_size(..=2557),
+            vec![
+                (PathBuf::from("/a/e/i"), 584),
+                (PathBuf::from("/a/g"), 2557)
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_dirs() {
+        let root = filesystem("$ cd /\n$ ls\ndir a\ndir b\n$ cd a\n$ ls\n1 f").unwrap();
+        assert_eq!(root.empty_dirs(), Vec::<PathBuf>::new());
+        assert_eq!(root.unexplored_dirs(), vec![PathBuf::from("/b")]);
+
+        let root =
+            filesystem("$ cd /\n$ ls\ndir a\ndir b\n$ cd b\n$ ls\n$ cd /\n$ cd a\n$ cd c").unwrap();
+        assert_eq!(root.empty_dirs(), vec![PathBuf::from("/b")]);
+        assert_eq!(
+            root.unexplored_dirs(),
+            vec![PathBuf::from("/a"), PathBuf::from("/a/c")]
+        );
+    }
+}
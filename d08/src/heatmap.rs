@@ -0,0 +1,169 @@
+use std::fmt::Write;
+
+use itertools::Itertools;
+
+use crate::{Direction, Directions, Forest, Tree};
+
+/// What to show for each tree
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Layer {
+    Height,
+    /// 1 if the tree can be seen from outside the forest on that side, 0 otherwise
+    VisibleFrom(Direction),
+    /// 1 if the tree can be seen from outside the forest on any of the sides, 0 otherwise
+    Visible(Directions),
+    ScenicScore(Directions),
+}
+
+impl Layer {
+    fn value(self, tree: &Tree) -> u64 {
+        match self {
+            Layer::Height => u64::from(tree.height),
+            Layer::VisibleFrom(direction) => u64::from(tree.is_visible_from(direction)),
+            Layer::Visible(directions) => u64::from(tree.is_visible(directions)),
+            Layer::ScenicScore(directions) => tree.scenic_score(directions) as u64,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Format {
+    /// plain (ASCII) grayscale Netpbm image
+    Pgm,
+    /// plain (ASCII) color Netpbm image, from black through red and yellow to white
+    Ppm,
+    /// the raw values, a row of the forest per line
+    Csv,
+}
+
+/// The highest gray level PGM allows
+const PGM_MAX: u64 = 65_535;
+
+/// Scales `value` from `0..=max` to `0..=to`
+fn scale(value: u64, max: u64, to: u64) -> u64 {
+    (u128::from(value) * u128::from(to) / u128::from(max.max(1))) as u64
+}
+
+/// A point on the black - red - yellow - white scale, for a value in `0..=255`
+fn heat(value: u64) -> [u64; 3] {
+    [
+        value * 3,
+        (value * 3).saturating_sub(255),
+        (value * 3).saturating_sub(510),
+    ]
+    .map(|channel| channel.min(255))
+}
+
+/// Renders one of the layers of the forest as an image or a matrix
+pub fn export(forest: &Forest, layer: Layer, format: Format) -> String {
+    let values: Vec<Vec<u64>> = forest
+        .rows()
+        .iter()
+        .map(|row| row.iter().map(|tree| layer.value(tree)).collect())
+        .collect();
+    let max = values.iter().flatten().copied().max().unwrap_or(0);
+    let (num_rows, num_cols) = (forest.num_rows(), forest.num_cols());
+
+    let mut out = String::new();
+    match format {
+        Format::Pgm => {
+            let max_gray = max.clamp(1, PGM_MAX);
+            writeln!(out, "P2\n{num_cols} {num_rows}\n{max_gray}").unwrap();
+            for row in &values {
+                let line = row
+                    .iter()
+                    .map(|&value| scale(value, max, max_gray))
+                    .join(" ");
+                writeln!(out, "{line}").unwrap();
+            }
+        }
+        Format::Ppm => {
+            writeln!(out, "P3\n{num_cols} {num_rows}\n255").unwrap();
+            for row in &values {
+                let line = row
+                    .iter()
+                    .flat_map(|&value| heat(scale(value, max, 255)))
+                    .join(" ");
+                writeln!(out, "{line}").unwrap();
+            }
+        }
+        Format::Csv => {
+            for row in &values {
+                writeln!(out, "{}", row.iter().join(",")).unwrap();
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+    use test_case::test_case;
+    const EXAMPLE: &str = include_str!("../inputs/example.txt");
+
+    #[test]
+    fn height_pgm() {
+        let forest = Forest::from_str(EXAMPLE).unwrap();
+        let expected = "\
+P2
+5 5
+9
+3 0 3 7 3
+2 5 5 1 2
+6 5 3 3 2
+3 3 5 4 9
+3 5 3 9 0
+";
+        assert_eq!(export(&forest, Layer::Height, Format::Pgm), expected);
+    }
+
+    #[test_case(Layer::VisibleFrom(Direction::Up) => "\
+1,1,1,1,1
+0,1,1,0,0
+1,0,0,0,0
+0,0,0,0,1
+0,0,0,1,0
+"; "visible from the top")]
+    #[test_case(Layer::Visible(Directions::Orthogonal) => "\
+1,1,1,1,1
+1,1,1,0,1
+1,1,0,1,1
+1,0,1,0,1
+1,1,1,1,1
+"; "visible")]
+    #[test_case(Layer::ScenicScore(Directions::Orthogonal) => "\
+0,0,0,0,0
+0,1,4,1,0
+0,6,1,2,0
+0,1,8,3,0
+0,0,0,0,0
+"; "scenic score")]
+    fn example_csv(layer: Layer) -> String {
+        export(&Forest::from_str(EXAMPLE).unwrap(), layer, Format::Csv)
+    }
+
+    #[test]
+    fn scenic_score_ppm() {
+        let forest = Forest::from_str(EXAMPLE).unwrap();
+        let ppm = export(
+            &forest,
+            Layer::ScenicScore(Directions::Orthogonal),
+            Format::Ppm,
+        );
+        let mut lines = ppm.lines();
+        assert_eq!(lines.next(), Some("P3"));
+        assert_eq!(lines.next(), Some("5 5"));
+        assert_eq!(lines.next(), Some("255"));
+        // the most scenic tree is white, those at the edge are black
+        let row_3: Vec<u64> = lines
+            .nth(3)
+            .unwrap()
+            .split(' ')
+            .map(|channel| channel.parse().unwrap())
+            .collect();
+        assert_eq!(row_3[..3], [0, 0, 0]);
+        assert_eq!(row_3[6..9], [255, 255, 255]);
+    }
+}
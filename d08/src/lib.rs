@@ -1,160 +1,308 @@
-use anyhow::{anyhow, Context};
+use std::{iter, str::FromStr};
+
+use anyhow::{anyhow, ensure, Context};
 use itertools::Itertools;
 
-/// Returns the transposed copy of a collection
-trait TransposeOutOfPlace {
-    fn transpose_out_of_place(self) -> Self;
+pub mod heatmap;
+pub mod vantage;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+    UpRight,
+    UpLeft,
+    DownRight,
+    DownLeft,
+}
+
+impl Direction {
+    /// (rows, columns) to go one tree further
+    fn offset(self) -> (isize, isize) {
+        match self {
+            Direction::Up => (-1, 0),
+            Direction::Down => (1, 0),
+            Direction::Left => (0, -1),
+            Direction::Right => (0, 1),
+            Direction::UpRight => (-1, 1),
+            Direction::UpLeft => (-1, -1),
+            Direction::DownRight => (1, 1),
+            Direction::DownLeft => (1, -1),
+        }
+    }
+
+    fn opposite(self) -> Self {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+            Direction::UpRight => Direction::DownLeft,
+            Direction::UpLeft => Direction::DownRight,
+            Direction::DownRight => Direction::UpLeft,
+            Direction::DownLeft => Direction::UpRight,
+        }
+    }
+}
+
+/// Which directions the elves look in
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Directions {
+    /// up, down, left and right, like in the puzzle
+    Orthogonal,
+    /// also along the diagonals
+    WithDiagonals,
 }
 
-impl<T> TransposeOutOfPlace for Vec<Vec<T>> {
-    /// stolen from: <https://stackoverflow.com/questions/39775060/reverse-iterating-over-a-vec-versus-vec-iter>
-    fn transpose_out_of_place(self) -> Self {
-        assert!(!self.is_empty());
-        let len = self[0].len();
-        let mut iters = self
-            .into_iter()
-            .map(IntoIterator::into_iter)
-            .collect::<Vec<_>>();
-        (0..len)
-            .map(|_| iters.iter_mut().map(|n| n.next().unwrap()).collect())
-            .collect()
+impl Directions {
+    /// Whether these directions include all of `other`
+    pub fn include(self, other: Directions) -> bool {
+        self == Directions::WithDiagonals || other == Directions::Orthogonal
+    }
+
+    pub fn directions(self) -> &'static [Direction] {
+        use Direction as D;
+        match self {
+            Directions::Orthogonal => &[D::Up, D::Down, D::Left, D::Right],
+            Directions::WithDiagonals => &[
+                D::Up,
+                D::Down,
+                D::Left,
+                D::Right,
+                D::UpRight,
+                D::UpLeft,
+                D::DownRight,
+                D::DownLeft,
+            ],
+        }
     }
 }
 
-struct Tree {
-    height: u32,
-    scenicity: usize,
+/// Number of directions a tree can look in
+const NUM_DIRECTIONS: usize = 8;
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Tree {
+    pub height: u32,
+    /// by direction - what the tree sees, for the directions the forest has been built with
+    sightlines: [Option<Sightline>; NUM_DIRECTIONS],
 }
+
 impl Tree {
     fn with_height(height: u32) -> Self {
         Self {
             height,
-            scenicity: 1,
+            sightlines: [None; NUM_DIRECTIONS],
         }
     }
 
-    fn is_visible(&self) -> bool {
-        self.scenicity == 0
+    fn sightline(&self, direction: Direction) -> Sightline {
+        self.sightlines[direction as usize]
+            .unwrap_or_else(|| panic!("the forest has been built without looking {direction:?}"))
     }
-    fn mark_visible(&mut self) {
-        self.scenicity = 0;
+
+    /// Whether all the trees between this one and the edge in `direction` are lower
+    pub fn is_visible_from(&self, direction: Direction) -> bool {
+        self.sightline(direction).to_the_edge
+    }
+
+    pub fn is_visible(&self, directions: Directions) -> bool {
+        directions
+            .directions()
+            .iter()
+            .any(|&direction| self.is_visible_from(direction))
+    }
+
+    /// Number of trees the tree sees in `direction`, up to the first one at least as high
+    pub fn viewing_distance(&self, direction: Direction) -> usize {
+        self.sightline(direction).viewing_distance
+    }
+
+    pub fn scenic_score(&self, directions: Directions) -> usize {
+        directions
+            .directions()
+            .iter()
+            .map(|&direction| self.viewing_distance(direction))
+            .product()
     }
 }
 
-fn forest(file: &str) -> anyhow::Result<Vec<Vec<Tree>>> {
-    file.lines()
-        .map(|line| {
-            line.chars()
-                .map(|char| match char.to_digit(10) {
-                    Some(n) => Ok(n + 1),
-                    None => Err(anyhow!("invalid height: {char}")),
-                })
-                .map_ok(Tree::with_height)
-                .try_collect()
-        })
-        .try_collect()
+/// The trees, by row and then by column, with what each of them sees
+/// in the directions the forest has been built with
+pub struct Forest {
+    trees: Vec<Vec<Tree>>,
+    directions: Directions,
 }
 
-/// Less exact than [`check_scenicities_in_a_line`] - checks whether
-/// each tree is visible, i.e. has a scenicity value of 0
-fn check_visibilities_in_a_line(line: &mut [Tree]) {
-    // save the highest tree of the line so that we
-    // don't check past it coming from both directions
-    let position_highest_tree = line.iter().position_max_by_key(|tree| tree.height).unwrap();
+/// Looks in all the directions, diagonals included
+impl FromStr for Forest {
+    type Err = anyhow::Error;
 
-    // check the line forwards until the highest tree
-    let mut current_max_height = u32::MIN;
-    for tree in &mut line[..position_highest_tree] {
-        if tree.height <= current_max_height {
-            continue;
-        }
-        current_max_height = tree.height;
-        tree.mark_visible();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s, Directions::WithDiagonals)
     }
+}
 
-    let mut current_max_height = u32::MIN;
-    for tree in line[position_highest_tree..].iter_mut().rev() {
-        if tree.height <= current_max_height {
-            continue;
+impl Forest {
+    /// Parses the forest, and works out what the trees see in the `directions` - and only those
+    pub fn new(s: &str, directions: Directions) -> anyhow::Result<Self> {
+        let mut trees: Vec<Vec<Tree>> = s
+            .lines()
+            .map(|line| {
+                line.chars()
+                    .map(|char| match char.to_digit(10) {
+                        Some(n) => Ok(Tree::with_height(n)),
+                        None => Err(anyhow!("invalid height: {char}")),
+                    })
+                    .try_collect()
+            })
+            .try_collect()?;
+        ensure!(
+            trees.iter().map(Vec::len).all_equal(),
+            "rows of different lengths"
+        );
+
+        for &direction in directions.directions() {
+            for_each_sightline(&mut trees, direction, |tree, sightline| {
+                tree.sightlines[direction as usize] = Some(sightline);
+            });
         }
-        current_max_height = tree.height;
-        tree.mark_visible();
+        Ok(Self { trees, directions })
     }
-}
 
-pub fn p1(file: &str) -> anyhow::Result<usize> {
-    // create the map
-    let mut forest: Vec<Vec<Tree>> = forest(file)?;
+    /// The directions the trees can be asked about
+    pub fn directions(&self) -> Directions {
+        self.directions
+    }
 
-    // analyze visibility horizontally
-    for row in &mut forest {
-        check_visibilities_in_a_line(row);
+    pub fn rows(&self) -> &[Vec<Tree>] {
+        &self.trees
     }
 
-    // transpose the map so that iterating vertically isn't so cache-miss-prone
-    forest = forest.transpose_out_of_place();
+    pub fn num_rows(&self) -> usize {
+        self.trees.len()
+    }
 
-    // analyze visibility vertically
-    for col in &mut forest {
-        check_visibilities_in_a_line(col);
+    pub fn num_cols(&self) -> usize {
+        self.trees.first().map_or(0, Vec::len)
     }
 
-    Ok(forest
-        .into_iter()
-        .flatten()
-        .filter(Tree::is_visible)
-        .count())
+    /// Every tree along with its (row, column)
+    pub fn trees(&self) -> impl Iterator<Item = ((usize, usize), &Tree)> {
+        self.trees.iter().enumerate().flat_map(|(row_idx, row)| {
+            row.iter()
+                .enumerate()
+                .map(move |(col_idx, tree)| ((row_idx, col_idx), tree))
+        })
+    }
 }
 
-/// More exact than [`check_visibilities_in_a_line`] - gets the exact scenicity values
-fn check_scenicities_in_a_line(line: &mut [Tree]) {
-    let scenicities = line
-        .iter()
-        .enumerate()
-        .map(|(tree_position, tree)| {
-            if tree.is_visible() {
-                return 0;
-            }
-
-            let current_scenicity_forward = line[(tree_position + 1)..]
-                .iter()
-                .take_while_inclusive(|other_tree| tree.height > other_tree.height)
-                .count();
-
-            let current_scenicity_backwards = line[..tree_position]
-                .iter()
-                .rev()
-                .take_while_inclusive(|other_tree| tree.height > other_tree.height)
-                .count();
+/// Coordinates of the trees on the way from `start` to the edge of the forest, going in `direction`
+fn line(
+    start: (usize, usize),
+    direction: Direction,
+    (num_rows, num_cols): (usize, usize),
+) -> impl Iterator<Item = (usize, usize)> {
+    let (row_offset, col_offset) = direction.offset();
+    iter::successors(Some(start), move |&(row, col)| {
+        let row = row.checked_add_signed(row_offset)?;
+        let col = col.checked_add_signed(col_offset)?;
+        (row < num_rows && col < num_cols).then_some((row, col))
+    })
+}
 
-            current_scenicity_backwards * current_scenicity_forward
+/// The trees where lines going in `direction` through the forest start
+fn line_starts(
+    direction: Direction,
+    (num_rows, num_cols): (usize, usize),
+) -> impl Iterator<Item = (usize, usize)> {
+    let (row_offset, col_offset) = direction.opposite().offset();
+    (0..num_rows)
+        .cartesian_product(0..num_cols)
+        .filter(move |&(row, col)| {
+            let row_before = row.checked_add_signed(row_offset);
+            let col_before = col.checked_add_signed(col_offset);
+            !matches!((row_before, col_before), (Some(row), Some(col)) if row < num_rows && col < num_cols)
         })
-        .collect_vec();
-
-    std::iter::zip(line, scenicities).for_each(|(tree, scenicity)| tree.scenicity *= scenicity);
 }
 
-pub fn p2(file: &str) -> anyhow::Result<usize> {
-    let mut forest: Vec<Vec<Tree>> = forest(file)?;
+/// What a tree sees looking in some direction
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+struct Sightline {
+    /// number of trees up to the first one at least as high, or to the edge
+    viewing_distance: usize,
+    /// whether all the trees up to the edge are lower, so that the tree can be seen from outside
+    to_the_edge: bool,
+}
 
-    for row in &mut forest {
-        check_scenicities_in_a_line(row);
+/// Calls `f` with what every tree sees looking in `direction`.
+/// Walks each line in the opposite direction once, keeping a stack of the trees passed so far
+/// that aren't hidden behind a closer one at least as high - so their heights decrease towards the top
+fn for_each_sightline(
+    forest: &mut [Vec<Tree>],
+    direction: Direction,
+    mut f: impl FnMut(&mut Tree, Sightline),
+) {
+    let dimensions = (forest.len(), forest.first().map_or(0, Vec::len));
+    let walking_direction = direction.opposite();
+    let mut stack: Vec<(u32, usize)> = Vec::new();
+    for start in line_starts(walking_direction, dimensions) {
+        stack.clear();
+        for (position, (row, col)) in line(start, walking_direction, dimensions).enumerate() {
+            let tree = &mut forest[row][col];
+            while stack
+                .last()
+                .is_some_and(|&(height, _)| height < tree.height)
+            {
+                stack.pop();
+            }
+            let sightline = match stack.last() {
+                Some(&(_, blocking_position)) => Sightline {
+                    viewing_distance: position - blocking_position,
+                    to_the_edge: false,
+                },
+                None => Sightline {
+                    viewing_distance: position,
+                    to_the_edge: true,
+                },
+            };
+            stack.push((tree.height, position));
+            f(tree, sightline);
+        }
     }
+}
 
-    forest = forest.transpose_out_of_place();
+/// Number of trees that can be seen from outside the forest, looking in any of the `directions`
+pub fn count_visible(file: &str, directions: Directions) -> anyhow::Result<usize> {
+    let forest = Forest::new(file, directions)?;
 
-    for col in &mut forest {
-        check_scenicities_in_a_line(col);
-    }
+    Ok(forest
+        .trees()
+        .filter(|(_, tree)| tree.is_visible(directions))
+        .count())
+}
+
+/// The highest product of the viewing distances in all the `directions` any tree has
+pub fn max_scenic_score(file: &str, directions: Directions) -> anyhow::Result<usize> {
+    let forest = Forest::new(file, directions)?;
 
     forest
-        .into_iter()
-        .flatten()
-        .map(|tree| tree.scenicity)
+        .trees()
+        .map(|(_, tree)| tree.scenic_score(directions))
         .max()
         .context("empty forest")
 }
 
+pub fn p1(file: &str) -> anyhow::Result<usize> {
+    count_visible(file, Directions::Orthogonal)
+}
+
+pub fn p2(file: &str) -> anyhow::Result<usize> {
+    max_scenic_score(file, Directions::Orthogonal)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -172,4 +320,53 @@ mod tests {
     fn test_p2(inp: &str) -> usize {
         p2(inp).unwrap()
     }
+
+    #[test]
+    #[should_panic(expected = "the forest has been built without looking UpLeft")]
+    fn only_orthogonal() {
+        let forest = Forest::new(EXAMPLE, Directions::Orthogonal).unwrap();
+        let (_, tree) = forest.trees().nth(6).unwrap();
+        assert_eq!(tree.scenic_score(Directions::Orthogonal), 1);
+        tree.viewing_distance(Direction::UpLeft);
+    }
+
+    /// Looks from every tree in every direction, one tree at a time
+    fn brute_force(inp: &str, directions: Directions) -> (usize, usize) {
+        let forest = Forest::from_str(inp).unwrap();
+        let forest = forest.rows();
+        let dimensions = (forest.len(), forest[0].len());
+        let (mut num_visible, mut max_score) = (0, 0);
+        for (row, col) in (0..dimensions.0).cartesian_product(0..dimensions.1) {
+            let height = forest[row][col].height;
+            let (mut visible, mut score) = (false, 1);
+            for &direction in directions.directions() {
+                let others = line((row, col), direction, dimensions)
+                    .skip(1)
+                    .collect_vec();
+                let viewing_distance = others
+                    .iter()
+                    .take_while_inclusive(|&&(r, c)| forest[r][c].height < height)
+                    .count();
+                visible |= others.iter().all(|&(r, c)| forest[r][c].height < height);
+                score *= viewing_distance;
+            }
+            num_visible += usize::from(visible);
+            max_score = max_score.max(score);
+        }
+        (num_visible, max_score)
+    }
+
+    #[test_case(EXAMPLE, Directions::Orthogonal; "example")]
+    #[test_case(REAL, Directions::Orthogonal; "real")]
+    #[test_case(EXAMPLE, Directions::WithDiagonals; "example with diagonals")]
+    #[test_case(REAL, Directions::WithDiagonals; "real with diagonals")]
+    fn matches_brute_force(inp: &str, directions: Directions) {
+        assert_eq!(
+            (
+                count_visible(inp, directions).unwrap(),
+                max_scenic_score(inp, directions).unwrap()
+            ),
+            brute_force(inp, directions)
+        );
+    }
 }
@@ -0,0 +1,132 @@
+use anyhow::{ensure, Context};
+use itertools::Itertools;
+
+use crate::{line, Direction, Directions, Forest, Tree};
+
+/// (row, column) of a tree
+pub type Coordinates = (usize, usize);
+
+impl Forest {
+    fn ensure_looking(&self, directions: Directions) -> anyhow::Result<()> {
+        ensure!(
+            self.directions().include(directions),
+            "the forest has been built looking {:?}, not {directions:?}",
+            self.directions()
+        );
+        Ok(())
+    }
+
+    fn tree(&self, (row, col): Coordinates) -> anyhow::Result<&Tree> {
+        self.rows()
+            .get(row)
+            .and_then(|trees| trees.get(col))
+            .with_context(|| format!("no tree at ({row}, {col})"))
+    }
+
+    /// The trees the tree at `coordinates` sees in each of the `directions`, closest first
+    pub fn visible_trees(
+        &self,
+        coordinates: Coordinates,
+        directions: Directions,
+    ) -> anyhow::Result<Vec<(Direction, Vec<Coordinates>)>> {
+        self.ensure_looking(directions)?;
+        let tree = self.tree(coordinates)?;
+        let dimensions = (self.num_rows(), self.num_cols());
+        Ok(directions
+            .directions()
+            .iter()
+            .map(|&direction| {
+                let visible = line(coordinates, direction, dimensions)
+                    .skip(1)
+                    .take(tree.viewing_distance(direction))
+                    .collect();
+                (direction, visible)
+            })
+            .collect())
+    }
+
+    /// The candidates along with their scenic scores, the most scenic first
+    pub fn rank(
+        &self,
+        candidates: &[Coordinates],
+        directions: Directions,
+    ) -> anyhow::Result<Vec<(Coordinates, usize)>> {
+        self.ensure_looking(directions)?;
+        let mut ranked: Vec<(Coordinates, usize)> = candidates
+            .iter()
+            .map(|&coordinates| {
+                let tree = self.tree(coordinates)?;
+                anyhow::Ok((coordinates, tree.scenic_score(directions)))
+            })
+            .try_collect()?;
+        ranked.sort_by_key(|&(coordinates, score)| (std::cmp::Reverse(score), coordinates));
+        Ok(ranked)
+    }
+
+    /// The `k` trees with the highest scenic scores, the most scenic first
+    pub fn most_scenic(&self, k: usize, directions: Directions) -> Vec<(Coordinates, usize)> {
+        self.trees()
+            .map(|(coordinates, tree)| (coordinates, tree.scenic_score(directions)))
+            .sorted_by_key(|&(coordinates, score)| (std::cmp::Reverse(score), coordinates))
+            .take(k)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+    const EXAMPLE: &str = include_str!("../inputs/example.txt");
+    const REAL: &str = include_str!("../inputs/real.txt");
+
+    #[test]
+    fn visible_trees() {
+        let forest = Forest::from_str(EXAMPLE).unwrap();
+        assert_eq!(
+            forest
+                .visible_trees((3, 2), Directions::Orthogonal)
+                .unwrap(),
+            vec![
+                (Direction::Up, vec![(2, 2), (1, 2)]),
+                (Direction::Down, vec![(4, 2)]),
+                (Direction::Left, vec![(3, 1), (3, 0)]),
+                (Direction::Right, vec![(3, 3), (3, 4)]),
+            ]
+        );
+        assert!(forest
+            .visible_trees((5, 0), Directions::Orthogonal)
+            .is_err());
+    }
+
+    #[test]
+    fn rank() {
+        let forest = Forest::from_str(EXAMPLE).unwrap();
+        assert_eq!(
+            forest
+                .rank(&[(0, 0), (1, 2), (3, 2)], Directions::Orthogonal)
+                .unwrap(),
+            vec![((3, 2), 8), ((1, 2), 4), ((0, 0), 0)]
+        );
+        assert!(forest.rank(&[(0, 5)], Directions::Orthogonal).is_err());
+
+        let forest = Forest::new(EXAMPLE, Directions::Orthogonal).unwrap();
+        assert!(forest.rank(&[(1, 2)], Directions::WithDiagonals).is_err());
+        assert!(forest
+            .visible_trees((1, 2), Directions::WithDiagonals)
+            .is_err());
+    }
+
+    #[test]
+    fn most_scenic() {
+        let forest = Forest::from_str(EXAMPLE).unwrap();
+        assert_eq!(
+            forest.most_scenic(3, Directions::Orthogonal),
+            vec![((3, 2), 8), ((2, 1), 6), ((1, 2), 4)]
+        );
+
+        let forest = Forest::from_str(REAL).unwrap();
+        let (_, max_score) = forest.most_scenic(1, Directions::Orthogonal)[0];
+        assert_eq!(max_score, 504_000);
+    }
+}
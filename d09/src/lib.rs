@@ -1,11 +1,14 @@
-use std::{collections::HashSet, iter, str::FromStr};
+use std::{collections::HashSet, hash::Hash, str::FromStr};
 
-use anyhow::{bail, Context};
-use libaoc::points::Point2D;
+use anyhow::{bail, ensure, Context};
+use libaoc::points::{Point2D, Point3D};
+
+pub mod trajectory;
 
 type Point = Point2D<i32>;
+type Point3 = Point3D<i32>;
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug)]
 enum Direction2D {
     Up,
     Down,
@@ -26,16 +29,46 @@ impl FromStr for Direction2D {
             "D" => Ok(Direction2D::Down),
             "L" => Ok(Direction2D::Left),
             "R" => Ok(Direction2D::Right),
+            "UR" => Ok(Direction2D::UpRight),
+            "UL" => Ok(Direction2D::UpLeft),
+            "DR" => Ok(Direction2D::DownRight),
+            "DL" => Ok(Direction2D::DownLeft),
+            s => bail!("invalid direction: {s}"),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+enum Direction3D {
+    Up,
+    Down,
+    Left,
+    Right,
+    Forward,
+    Backward,
+}
+
+impl FromStr for Direction3D {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "U" => Ok(Direction3D::Up),
+            "D" => Ok(Direction3D::Down),
+            "L" => Ok(Direction3D::Left),
+            "R" => Ok(Direction3D::Right),
+            "F" => Ok(Direction3D::Forward),
+            "B" => Ok(Direction3D::Backward),
             s => bail!("invalid direction: {s}"),
         }
     }
 }
 
-trait Move2D {
-    fn r#move(&mut self, direction: Direction2D) -> &mut Self;
+trait Move<D> {
+    fn r#move(&mut self, direction: D) -> &mut Self;
 }
 
-impl Move2D for Point {
+impl Move<Direction2D> for Point {
     fn r#move(&mut self, direction: Direction2D) -> &mut Self {
         use Direction2D as D;
         match direction {
@@ -55,44 +88,180 @@ impl Move2D for Point {
             D::DownLeft => {
                 self.r#move(D::Down).r#move(D::Left);
             }
-        };
+        }
+        self
+    }
+}
+
+impl Move<Direction3D> for Point3 {
+    fn r#move(&mut self, direction: Direction3D) -> &mut Self {
+        use Direction3D as D;
+        match direction {
+            D::Up => self.1 += 1,
+            D::Down => self.1 -= 1,
+            D::Left => self.0 -= 1,
+            D::Right => self.0 += 1,
+            D::Forward => self.2 += 1,
+            D::Backward => self.2 -= 1,
+        }
         self
     }
 }
 
-fn inner(file: &str, rope_len: usize) -> anyhow::Result<usize> {
-    let mut rope = Rope::with_length(rope_len);
-    let mut movement_directions = Vec::with_capacity(file.lines().count());
-    for line in file.lines() {
-        let (direction, num_repeats) = line.split_once(' ').context("expected space")?;
-        let direction = Direction2D::from_str(direction)?;
-        let num_repeats = usize::from_str(num_repeats)?;
-        movement_directions.extend(iter::repeat(direction).take(num_repeats));
+/// A point a knot of a rope can be at
+trait Knot: Copy + Eq + Hash + Move<Self::Direction> {
+    type Direction: Copy + FromStr<Err = anyhow::Error>;
+
+    /// where all the knots start
+    const ORIGIN: Self;
+
+    /// Moves the knot one step closer to `leader`, unless they touch already.
+    /// Returns whether the knot has moved
+    fn catch_up(&mut self, leader: Self) -> bool;
+
+    /// Moves the knot `num_steps` times in `direction` at once
+    fn advance(&mut self, direction: Self::Direction, num_steps: i32);
+}
+
+impl Knot for Point {
+    type Direction = Direction2D;
+
+    const ORIGIN: Self = Point2D(0, 0);
+
+    fn catch_up(&mut self, leader: Self) -> bool {
+        use Direction2D as D;
+        let move_to_catch_up = match (leader.0 - self.0, leader.1 - self.1) {
+            // knots touch - no catching-up necessary
+            (-1..=1, -1..=1) => return false,
+            // catch-up diagonally
+            (1 | 2, 1 | 2) => D::UpRight,
+            (-1 | -2, 1 | 2) => D::UpLeft,
+            (1 | 2, -1 | -2) => D::DownRight,
+            (-1 | -2, -1 | -2) => D::DownLeft,
+            // catch-up vertically/horizontally
+            (0, 2) => D::Up,
+            (0, -2) => D::Down,
+            (2, 0) => D::Right,
+            (-2, 0) => D::Left,
+            _ => unreachable!(),
+        };
+        self.r#move(move_to_catch_up);
+        true
+    }
+
+    fn advance(&mut self, direction: Direction2D, num_steps: i32) {
+        let mut step = Self::ORIGIN;
+        step.r#move(direction);
+        self.0 += step.0 * num_steps;
+        self.1 += step.1 * num_steps;
+    }
+}
+
+impl Knot for Point3 {
+    type Direction = Direction3D;
+
+    const ORIGIN: Self = Point3D(0, 0, 0);
+
+    fn catch_up(&mut self, leader: Self) -> bool {
+        let diff = [leader.0 - self.0, leader.1 - self.1, leader.2 - self.2];
+        // knots touch, diagonally included - no catching-up necessary
+        if diff
+            .iter()
+            .all(|coordinate_diff| coordinate_diff.abs() <= 1)
+        {
+            return false;
+        }
+        // get one step closer along every axis where the knots differ
+        self.0 += diff[0].signum();
+        self.1 += diff[1].signum();
+        self.2 += diff[2].signum();
+        true
     }
 
+    fn advance(&mut self, direction: Direction3D, num_steps: i32) {
+        let mut step = Self::ORIGIN;
+        step.r#move(direction);
+        self.0 += step.0 * num_steps;
+        self.1 += step.1 * num_steps;
+        self.2 += step.2 * num_steps;
+    }
+}
+
+/// The moves of the head: the directions, along with how many steps to take in each
+fn segments<D: FromStr<Err = anyhow::Error>>(file: &str) -> anyhow::Result<Vec<(D, usize)>> {
+    file.lines()
+        .map(|line| {
+            let (direction, num_repeats) = line.split_once(' ').context("expected space")?;
+            Ok((D::from_str(direction)?, usize::from_str(num_repeats)?))
+        })
+        .collect()
+}
+
+/// The moves of the head, one step at a time
+#[cfg(test)]
+fn movement_directions<D: Copy + FromStr<Err = anyhow::Error>>(
+    file: &str,
+) -> anyhow::Result<Vec<D>> {
+    Ok(segments(file)?
+        .into_iter()
+        .flat_map(|(direction, num_repeats)| std::iter::repeat_n(direction, num_repeats))
+        .collect())
+}
+
+fn inner<K: Knot>(file: &str, rope_len: usize) -> anyhow::Result<usize> {
+    ensure!(rope_len > 0, "a rope needs at least one knot");
+    let mut rope: Rope<K> = Rope::with_length(rope_len);
+    let mut before_step = rope.clone();
+
     let mut visited_positions = HashSet::new();
-    visited_positions.insert(Point2D(0, 0));
-    for direction in movement_directions {
-        rope.r#move(direction);
-        visited_positions.insert(*rope.last().unwrap());
+    visited_positions.insert(K::ORIGIN);
+    for (direction, num_steps) in segments::<K::Direction>(file)? {
+        let mut steps_left = num_steps;
+        while steps_left > 0 {
+            before_step.clone_from(&rope);
+            rope.r#move(direction);
+            visited_positions.insert(*rope.last().unwrap());
+            steps_left -= 1;
+
+            // once a step moves the whole rope along with the head, the knots are where they
+            // were relative to each other, so every further step does the same
+            let moved_along = rope.iter().zip(&before_step).all(|(&knot, &before)| {
+                let mut moved = before;
+                moved.r#move(direction);
+                knot == moved
+            });
+            if moved_along {
+                let steps_left = i32::try_from(steps_left).context("move too long")?;
+                let tail = *rope.last().unwrap();
+                visited_positions.extend((1..=steps_left).map(|num_steps| {
+                    let mut position = tail;
+                    position.advance(direction, num_steps);
+                    position
+                }));
+                for knot in &mut rope {
+                    knot.advance(direction, steps_left);
+                }
+                break;
+            }
+        }
     }
 
     Ok(visited_positions.len())
 }
 
-type Rope = Vec<Point>;
+type Rope<K = Point> = Vec<K>;
 trait RopeTrait {
     fn with_length(len: usize) -> Self;
 }
 
-impl RopeTrait for Rope {
+impl<K: Knot> RopeTrait for Rope<K> {
     fn with_length(len: usize) -> Self {
-        vec![Point2D(0, 0); len]
+        vec![K::ORIGIN; len]
     }
 }
 
-impl Move2D for Rope {
-    fn r#move(&mut self, direction: Direction2D) -> &mut Self {
+impl<K: Knot> Move<K::Direction> for Rope<K> {
+    fn r#move(&mut self, direction: K::Direction) -> &mut Self {
         // take the head and just move it
         let head = self.first_mut().unwrap();
         head.r#move(direction);
@@ -102,33 +271,15 @@ impl Move2D for Rope {
         let mut prev = *head;
 
         for curr in self.iter_mut().skip(1) {
-            use Direction2D as D;
-            let move_to_catch_up = match (prev.0 - curr.0, prev.1 - curr.1) {
-                // knots touch - no catching-up necessary
-                (-1..=1, -1..=1) => None,
-                // catch-up diagonally
-                (1 | 2, 1 | 2) => Some(D::UpRight),
-                (-1 | -2, 1 | 2) => Some(D::UpLeft),
-                (1 | 2, -1 | -2) => Some(D::DownRight),
-                (-1 | -2, -1 | -2) => Some(D::DownLeft),
-                // catch-up vertically/horizontally
-                (0, 2) => Some(D::Up),
-                (0, -2) => Some(D::Down),
-                (2, 0) => Some(D::Right),
-                (-2, 0) => Some(D::Left),
-                _ => unreachable!(),
-            };
-
-            match move_to_catch_up {
-                // check the distance to the previous knot
-                // - if too big, teleport to it
-                Some(direction) => curr.r#move(direction),
-                // - otherwise:
-                // 1. don't move at all
-                // 2. observe that all the necessary pulls have already been made and
-                //    the rest of the rope doesn't need to move, so don't check further
-                None => break,
-            };
+            // check the distance to the previous knot
+            // - if too big, catch up with it
+            // - otherwise:
+            // 1. don't move at all
+            // 2. observe that all the necessary pulls have already been made and
+            //    the rest of the rope doesn't need to move, so don't check further
+            if !curr.catch_up(prev) {
+                break;
+            }
             prev = *curr;
         }
         self
@@ -136,16 +287,24 @@ impl Move2D for Rope {
 }
 
 pub fn p1(file: &str) -> anyhow::Result<usize> {
-    inner(file, 2)
+    inner::<Point>(file, 2)
 }
 
 pub fn p2(file: &str) -> anyhow::Result<usize> {
-    inner(file, 10)
+    inner::<Point>(file, 10)
+}
+
+/// Number of positions the tail of a rope with `rope_len` knots visits in 3D,
+/// with `F` and `B` moving along the third axis
+pub fn tail_positions_3d(file: &str, rope_len: usize) -> anyhow::Result<usize> {
+    inner::<Point3>(file, rope_len)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::{collection::vec, prelude::*};
+    use std::fmt::Write;
     use test_case::test_case;
     const EX1: &str = include_str!("../inputs/examples/1");
     const EX2: &str = include_str!("../inputs/examples/2");
@@ -153,7 +312,7 @@ mod tests {
 
     #[test]
     fn move_2_rope() {
-        let mut rope = Rope::with_length(2);
+        let mut rope: Rope = Rope::with_length(2);
         rope.r#move(Direction2D::Up);
         assert_eq!(rope, vec![Point2D(0, 1), Point2D(0, 0)]);
     }
@@ -168,4 +327,177 @@ mod tests {
     fn test_p2(inp: &str) -> usize {
         p2(inp).unwrap()
     }
+
+    #[test]
+    fn diagonal_moves() {
+        let directions = movement_directions("UR 3\nDL 1").unwrap();
+        let mut rope: Rope = Rope::with_length(3);
+        for direction in directions {
+            rope.r#move(direction);
+        }
+        assert_eq!(rope, vec![Point2D(2, 2), Point2D(2, 2), Point2D(1, 1)]);
+    }
+
+    #[test]
+    fn invalid_direction() {
+        assert!(movement_directions::<Direction2D>("RU 3").is_err());
+        assert!(movement_directions::<Direction3D>("UR 3").is_err());
+    }
+
+    #[test_case(EX1, 2; "example, short rope")]
+    #[test_case(EX2, 10; "example, long rope")]
+    #[test_case(REAL, 10; "real")]
+    fn flat_3d_rope(inp: &str, rope_len: usize) {
+        assert_eq!(
+            tail_positions_3d(inp, rope_len).unwrap(),
+            inner::<Point>(inp, rope_len).unwrap()
+        );
+        // the same moves, only on another plane
+        let forward_backward = inp.replace('U', "F").replace('D', "B");
+        assert_eq!(
+            tail_positions_3d(&forward_backward, rope_len).unwrap(),
+            inner::<Point>(inp, rope_len).unwrap()
+        );
+    }
+
+    #[test]
+    fn no_knots() {
+        assert!(tail_positions_3d("U 1", 0).is_err());
+        assert!(inner::<Point>("", 0).is_err());
+    }
+
+    #[test]
+    fn moves_3d() {
+        let directions = movement_directions("F 2\nU 2").unwrap();
+        let mut rope: Rope<Point3> = Rope::with_length(3);
+        for direction in directions {
+            rope.r#move(direction);
+        }
+        assert_eq!(
+            rope,
+            vec![Point3D(0, 2, 2), Point3D(0, 1, 2), Point3D(0, 1, 1)]
+        );
+        assert_eq!(tail_positions_3d("F 2\nU 2", 2).unwrap(), 3);
+    }
+
+    /// The tail positions, simulating the moves one step at a time
+    fn step_wise<K: Knot>(inp: &str, rope_len: usize) -> usize {
+        let mut rope: Rope<K> = Rope::with_length(rope_len);
+        let mut visited_positions = HashSet::from([K::ORIGIN]);
+        for direction in movement_directions(inp).unwrap() {
+            rope.r#move(direction);
+            visited_positions.insert(*rope.last().unwrap());
+        }
+        visited_positions.len()
+    }
+
+    #[test_case(EX1, 2; "example, short rope")]
+    #[test_case(EX2, 10; "example, long rope")]
+    #[test_case(REAL, 2; "real, short rope")]
+    #[test_case(REAL, 10; "real, long rope")]
+    #[test_case(REAL, 1; "head only")]
+    fn segments_match_step_wise(inp: &str, rope_len: usize) {
+        assert_eq!(
+            inner::<Point>(inp, rope_len).unwrap(),
+            step_wise::<Point>(inp, rope_len)
+        );
+        assert_eq!(
+            inner::<Point3>(inp, rope_len).unwrap(),
+            step_wise::<Point3>(inp, rope_len)
+        );
+    }
+
+    #[test]
+    fn huge_moves() {
+        // the tail stops 9 cells short of where the head turns, both ways
+        assert_eq!(p2("R 2000000\nL 4000000").unwrap(), 1_999_992 + 1_999_991);
+        // the turn pulls the whole diagonal rope one step further
+        assert_eq!(p2("UR 1000000\nD 3").unwrap(), 999_993);
+    }
+
+    fn direction() -> impl Strategy<Value = Direction2D> {
+        use Direction2D as D;
+        prop_oneof![
+            Just(D::Up),
+            Just(D::Down),
+            Just(D::Left),
+            Just(D::Right),
+            Just(D::UpRight),
+            Just(D::UpLeft),
+            Just(D::DownRight),
+            Just(D::DownLeft),
+        ]
+    }
+
+    fn label(direction: Direction2D) -> &'static str {
+        use Direction2D as D;
+        match direction {
+            D::Up => "U",
+            D::Down => "D",
+            D::Left => "L",
+            D::Right => "R",
+            D::UpRight => "UR",
+            D::UpLeft => "UL",
+            D::DownRight => "DR",
+            D::DownLeft => "DL",
+        }
+    }
+
+    fn direction_3d() -> impl Strategy<Value = Direction3D> {
+        use Direction3D as D;
+        prop_oneof![
+            Just(D::Up),
+            Just(D::Down),
+            Just(D::Left),
+            Just(D::Right),
+            Just(D::Forward),
+            Just(D::Backward),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn knots_stay_together(
+            rope_len in 2..12usize,
+            directions in vec(direction(), 0..200),
+        ) {
+            let mut rope: Rope = Rope::with_length(rope_len);
+            for direction in directions {
+                rope.r#move(direction);
+                for (knot, next) in rope.iter().zip(rope.iter().skip(1)) {
+                    prop_assert!((knot.0 - next.0).abs() <= 1 && (knot.1 - next.1).abs() <= 1);
+                }
+            }
+        }
+
+        #[test]
+        fn random_segments_match_step_wise(
+            rope_len in 1..12usize,
+            segments in vec((direction(), 0..40usize), 0..30),
+        ) {
+            let inp = segments.iter().fold(String::new(), |mut inp, (direction, num_steps)| {
+                writeln!(inp, "{} {num_steps}", label(*direction)).unwrap();
+                inp
+            });
+            prop_assert_eq!(inner::<Point>(&inp, rope_len).unwrap(), step_wise::<Point>(&inp, rope_len));
+        }
+
+        #[test]
+        fn knots_stay_together_3d(
+            rope_len in 2..12usize,
+            directions in vec(direction_3d(), 0..200),
+        ) {
+            let mut rope: Rope<Point3> = Rope::with_length(rope_len);
+            for direction in directions {
+                rope.r#move(direction);
+                for (knot, next) in rope.iter().zip(rope.iter().skip(1)) {
+                    prop_assert!(
+                        (knot.0 - next.0).abs() <= 1
+                            && (knot.1 - next.1).abs() <= 1
+                            && (knot.2 - next.2).abs() <= 1
+                    );
+                }
+            }
+        }
+    }
 }
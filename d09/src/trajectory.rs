@@ -0,0 +1,302 @@
+use std::collections::HashSet;
+
+use anyhow::ensure;
+use libaoc::points::Point2D;
+
+use crate::{segments, Direction2D, Move, Point, Rope, RopeTrait};
+
+/// A stretch of steps during which a knot moves by the same offset every step - which might be none at all
+struct Run {
+    /// the step the run starts at
+    start: usize,
+    /// where the knot is at `start`
+    from: Point,
+    /// how far the knot moves in each step
+    delta: Point,
+    /// number of steps
+    len: usize,
+}
+
+impl Run {
+    /// Where the knot is after `step` steps, for a step within the run
+    fn position(&self, step: usize) -> Point {
+        let num_steps = (step - self.start) as i32;
+        Point2D(
+            self.from.0 + self.delta.0 * num_steps,
+            self.from.1 + self.delta.1 * num_steps,
+        )
+    }
+
+    fn end(&self) -> Point {
+        self.position(self.start + self.len)
+    }
+}
+
+/// Positions of every knot of a rope after every step, the initial state being step 0
+pub struct Trajectory {
+    /// for each knot, the runs it's made, one after another.
+    /// Knots tend to keep moving the same way for a while, or to stay put, so there are far fewer runs than steps
+    runs: Vec<Vec<Run>>,
+    num_steps: usize,
+}
+
+impl Trajectory {
+    /// Moves a rope with `rope_len` knots according to the input, recording each step
+    pub fn record(file: &str, rope_len: usize) -> anyhow::Result<Self> {
+        ensure!(rope_len > 0, "a rope needs at least one knot");
+        let mut rope: Rope = Rope::with_length(rope_len);
+        let mut runs: Vec<Vec<Run>> = rope
+            .iter()
+            .map(|&knot| {
+                vec![Run {
+                    start: 0,
+                    from: knot,
+                    delta: Point2D(0, 0),
+                    len: 0,
+                }]
+            })
+            .collect();
+
+        let mut num_steps = 0;
+        for (direction, num_repeats) in segments::<Direction2D>(file)? {
+            for _ in 0..num_repeats {
+                rope.r#move(direction);
+                for (knot_runs, &knot) in runs.iter_mut().zip(&rope) {
+                    let last = knot_runs.last_mut().expect("every knot starts with a run");
+                    let end = last.end();
+                    let delta = Point2D(knot.0 - end.0, knot.1 - end.1);
+                    if delta == last.delta {
+                        last.len += 1;
+                    } else {
+                        knot_runs.push(Run {
+                            start: num_steps,
+                            from: end,
+                            delta,
+                            len: 1,
+                        });
+                    }
+                }
+                num_steps += 1;
+            }
+        }
+
+        Ok(Self { runs, num_steps })
+    }
+
+    pub fn num_steps(&self) -> usize {
+        self.num_steps
+    }
+
+    pub fn num_knots(&self) -> usize {
+        self.runs.len()
+    }
+
+    /// Where the `knot`th knot (the head being the 0th one) is after `step` steps
+    pub fn position(&self, step: usize, knot: usize) -> Option<Point> {
+        if step > self.num_steps {
+            return None;
+        }
+        let knot_runs = self.runs.get(knot)?;
+        let run_idx = knot_runs
+            .partition_point(|run| run.start < step)
+            .saturating_sub(1);
+        Some(knot_runs[run_idx].position(step))
+    }
+
+    /// Every position the `knot`th knot has been in
+    pub fn visited(&self, knot: usize) -> HashSet<Point> {
+        self.runs
+            .get(knot)
+            .map(|knot_runs| {
+                knot_runs
+                    .iter()
+                    .flat_map(|run| {
+                        (run.start..=run.start + run.len).map(|step| run.position(step))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// The smallest rectangle (lower left and upper right corner) holding every position of every knot
+    fn bounds(&self) -> (Point, Point) {
+        self.runs
+            .iter()
+            .flatten()
+            // knots move in straight lines during a run, so it's enough to look at where the runs start and end
+            .flat_map(|run| [run.from, run.end()])
+            .fold(
+                (Point2D(0, 0), Point2D(0, 0)),
+                |(min, max), Point2D(x, y)| {
+                    (
+                        Point2D(min.0.min(x), min.1.min(y)),
+                        Point2D(max.0.max(x), max.1.max(y)),
+                    )
+                },
+            )
+    }
+
+    /// Draws the area the rope moves in, going row by row from the top
+    fn render(&self, cell: impl Fn(Point) -> char) -> String {
+        let (min, max) = self.bounds();
+        (min.1..=max.1)
+            .rev()
+            .map(|y| {
+                (min.0..=max.0)
+                    .map(|x| cell(Point2D(x, y)))
+                    .collect::<String>()
+                    + "\n"
+            })
+            .collect()
+    }
+
+    /// How the puzzle marks the `knot`th knot
+    fn label(&self, knot: usize) -> char {
+        match knot {
+            0 => 'H',
+            1 if self.num_knots() == 2 => 'T',
+            // digits, and then letters for ropes longer than 10 knots
+            _ => char::from_digit(knot as u32, 36).unwrap_or('?'),
+        }
+    }
+
+    /// Draws the rope after `step` steps the way the puzzle does: the knots closer to the head
+    /// cover those further away, and `s` marks the starting position if it's free
+    pub fn render_step(&self, step: usize) -> Option<String> {
+        let knots = (0..self.num_knots())
+            .map(|knot| self.position(step, knot))
+            .collect::<Option<Vec<_>>>()?;
+        Some(
+            self.render(|cell| match knots.iter().position(|&knot| knot == cell) {
+                Some(knot) => self.label(knot),
+                None if cell == Point2D(0, 0) => 's',
+                None => '.',
+            }),
+        )
+    }
+
+    /// Draws the positions the tail has been in as `#`, apart from the starting position marked `s`
+    pub fn render_visited(&self) -> String {
+        let visited = self.visited(self.num_knots() - 1);
+        self.render(|cell| match cell {
+            Point2D(0, 0) => 's',
+            cell if visited.contains(&cell) => '#',
+            _ => '.',
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::movement_directions;
+    use test_case::test_case;
+    const EX1: &str = include_str!("../inputs/examples/1");
+    const EX2: &str = include_str!("../inputs/examples/2");
+    const REAL: &str = include_str!("../inputs/real.txt");
+
+    #[test_case(1 => "\
+......
+......
+......
+......
+TH....
+"; "first step")]
+    #[test_case(3 => "\
+......
+......
+......
+......
+s.TH..
+"; "third step")]
+    #[test_case(24 => "\
+......
+......
+.TH...
+......
+s.....
+"; "last step")]
+    fn render_step(step: usize) -> String {
+        Trajectory::record(EX1, 2)
+            .unwrap()
+            .render_step(step)
+            .unwrap()
+    }
+
+    #[test]
+    fn render_step_long_rope() {
+        let trajectory = Trajectory::record(EX1, 10).unwrap();
+        // after the first four moves right
+        assert_eq!(
+            trajectory.render_step(4).unwrap(),
+            "\
+......
+......
+......
+......
+4321H.
+"
+        );
+        assert_eq!(trajectory.render_step(25), None);
+    }
+
+    #[test]
+    fn render_visited() {
+        assert_eq!(
+            Trajectory::record(EX1, 2).unwrap().render_visited(),
+            "\
+..##..
+...##.
+.####.
+....#.
+s###..
+"
+        );
+    }
+
+    #[test_case(EX1, 2 => 13)]
+    #[test_case(REAL, 2 => 5960)]
+    #[test_case(EX1, 10 => 1)]
+    #[test_case(EX2, 10 => 36)]
+    #[test_case(REAL, 10 => 2327)]
+    fn tail_visited(inp: &str, rope_len: usize) -> usize {
+        Trajectory::record(inp, rope_len)
+            .unwrap()
+            .visited(rope_len - 1)
+            .len()
+    }
+
+    #[test]
+    fn no_knots() {
+        assert!(Trajectory::record("R 1", 0).is_err());
+        assert!(Trajectory::record("", 0).is_err());
+    }
+
+    #[test]
+    fn runs_per_segment() {
+        let trajectory = Trajectory::record("R 1000\nU 1000", 2).unwrap();
+        // staying at the start, and then one run for each segment
+        assert_eq!(trajectory.runs[0].len(), 3);
+        assert_eq!(trajectory.position(1500, 0), Some(Point2D(1000, 500)));
+        assert_eq!(trajectory.position(1500, 1), Some(Point2D(1000, 499)));
+    }
+
+    #[test_case(EX2, 10; "example")]
+    #[test_case(REAL, 10; "real")]
+    #[test_case("U 5\nR 3\nR 0\nU 1", 3; "turning")]
+    fn positions_match_step_by_step(inp: &str, rope_len: usize) {
+        let trajectory = Trajectory::record(inp, rope_len).unwrap();
+        assert_eq!(trajectory.position(0, rope_len - 1), Some(Point2D(0, 0)));
+        let mut rope: Rope = Rope::with_length(rope_len);
+        for (step, direction) in movement_directions::<Direction2D>(inp)
+            .unwrap()
+            .into_iter()
+            .enumerate()
+        {
+            rope.r#move(direction);
+            for (knot, &position) in rope.iter().enumerate() {
+                assert_eq!(trajectory.position(step + 1, knot), Some(position));
+            }
+        }
+    }
+}